@@ -125,7 +125,43 @@ use types::ErrorStackDeriveInput;
 /// context types in [`error-stack`] [`Report`]s without all the boilerplate.
 ///
 /// The macro has a `display` attribute, which specifies a formatting string to
-/// print a value of the given type or enum variant.
+/// print a value of the given type or enum variant. Formatting strings follow
+/// the same syntax as [`format!`], except that placeholders (including `width$`
+/// and `precision$` arguments) refer to the fields of the type or variant by
+/// name or index. Placeholders can also access nested fields and call methods
+/// that take no arguments, as in `{req.id}` or `{items.len()}`.
+///
+/// Extra arguments can follow the formatting string, just like in [`format!`].
+/// Implicit positional placeholders (`{}`) refer to these arguments in order,
+/// and named arguments take precedence over fields with the same name. In enum
+/// variants, arguments can use the variant's fields by name, or as `_field0`,
+/// `_field1`, etc. for tuple variants.
+///
+/// For generic types, the macro adds the required trait bounds (such as
+/// `T: Display` for `{value}` or `T::Id: LowerHex` for `{id:x}`) to the
+/// generated implementations, based on the fields that are interpolated. The
+/// [`Error`] implementation additionally requires the type to implement
+/// [`Debug`].
+///
+/// Since [`error-stack`] requires context types to be `Send + Sync + 'static`,
+/// the macro also checks that every field of the type implements these traits,
+/// reporting an error at the offending field otherwise. Fields whose types
+/// mention a type parameter are not checked. Types that are never used in a
+/// [`Report`] can opt out with `#[error(no_context_check)]`.
+///
+/// Uninhabited types don't need a `display` attribute, and one given anyway is
+/// reported with a warning, since it is never used. These are enums without
+/// variants, structs with a field of type `!` or [`Infallible`], and enums
+/// where every variant has such a field. The macro cannot resolve imports or
+/// type aliases, so [`Infallible`] is only recognized when written as
+/// `Infallible`, `core::convert::Infallible` or `std::convert::Infallible`.
+/// Fields of other uninhabited types can be marked with
+/// `#[error(uninhabited)]`.
+///
+/// Only the `cfg`, `cfg_attr` and lint attributes of the type and its variants
+/// are copied onto the generated code, with `expect` turned into `allow`.
+/// Other attributes can be forwarded with
+/// `#[error(forward_attrs(name, ...))]`.
 ///
 /// # Examples
 ///
@@ -155,6 +191,122 @@ use types::ErrorStackDeriveInput;
 /// }
 /// ```
 ///
+/// The enum-level default is expanded for every variant without its own
+/// `#[display]` attribute, so it can refer to fields those variants share.
+///
+/// ```
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Debug, Error)]
+/// #[display("database error in {table}")]
+/// enum DatabaseError {
+///     Query { table: String, query: String },
+///     Insert { table: String },
+/// }
+///
+/// let err = DatabaseError::Insert { table: "users".to_string() };
+/// assert_eq!(err.to_string(), "database error in users");
+/// ```
+///
+/// ## Messages from variant names
+///
+/// `#[display(auto)]` derives the message of every variant without its own
+/// `display` attribute from the variant name. The case style can be set with
+/// `case` (`"lower case"` by default, `"Sentence case"`, `"snake_case"`,
+/// `"kebab-case"` or `"SCREAMING_SNAKE_CASE"`), and `prefix` and `suffix` are
+/// added around the message. The same attribute can also be used on a single
+/// variant.
+///
+/// ```
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Debug, Error)]
+/// #[display(auto, prefix = "storage: ")]
+/// enum StorageError {
+///     NotFound,
+///     PermissionDenied,
+///
+///     #[display(auto, case = "SCREAMING_SNAKE_CASE")]
+///     InvalidInput,
+/// }
+///
+/// assert_eq!(StorageError::NotFound.to_string(), "storage: not found");
+/// assert_eq!(StorageError::InvalidInput.to_string(), "INVALID_INPUT");
+/// ```
+///
+/// ## Messages from doc comments
+///
+/// `#[display(doc)]` uses the first paragraph of the doc comment of the struct
+/// as its template. On an enum, it does the same for every variant without its
+/// own `display` attribute. The template is checked and interpolated like any
+/// other, so the documentation and the message stay the same.
+///
+/// ```
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Debug, Error)]
+/// #[display(doc)]
+/// enum StorageError {
+///     /// file `{0}` was not found
+///     ///
+///     /// This paragraph is only documentation.
+///     NotFound(String),
+///
+///     #[display("permission denied")]
+///     PermissionDenied,
+/// }
+///
+/// let err = StorageError::NotFound("data.bin".to_string());
+/// assert_eq!(err.to_string(), "file `data.bin` was not found");
+/// ```
+///
+/// ## Intrinsic placeholders
+///
+/// `{type}`, `{variant}` and `{module}` are replaced with the name of the type,
+/// the name of the enum variant and [`module_path!()`] respectively. Write the
+/// placeholder as a raw identifier (`{r#variant}`) to refer to a field with
+/// the same name instead; the bare placeholder is rejected when such a field
+/// exists.
+///
+/// ```
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Debug, Error)]
+/// #[display("{type}::{variant} failed")]
+/// enum StorageError {
+///     Read,
+///
+///     #[display("{type}::Write failed on {r#variant}")]
+///     Write { variant: u8 },
+/// }
+///
+/// assert_eq!(StorageError::Read.to_string(), "StorageError::Read failed");
+///
+/// let err = StorageError::Write { variant: 2 };
+/// assert_eq!(err.to_string(), "StorageError::Write failed on 2");
+/// ```
+///
+/// ## Transparent wrapper
+///
+/// `#[display(transparent)]` forwards formatting to the only field of a struct
+/// or enum variant, using its [`Display`] implementation.
+///
+/// ```
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Debug, Error)]
+/// enum ConfigError {
+///     #[display(transparent)]
+///     Io(std::io::Error),
+///
+///     #[display("invalid config")]
+///     Invalid,
+/// }
+///
+/// let err = ConfigError::Io(std::io::Error::other("disk full"));
+/// assert_eq!(err.to_string(), "disk full");
+/// ```
+///
 /// ## Field interpolation (discouraged)
 ///
 /// ```
@@ -168,6 +320,281 @@ use types::ErrorStackDeriveInput;
 /// assert_eq!(err.to_string(), "invalid card string: \"1234567\"");
 /// ```
 ///
+/// ## Extra arguments (discouraged)
+///
+/// ```
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Debug, Error)]
+/// #[display(
+///     "expected {} bytes, got {actual}",
+///     self.expected.len(),
+///     actual = self.buf.len(),
+/// )]
+/// struct LengthError {
+///     expected: Vec<u8>,
+///     buf: Vec<u8>,
+/// }
+///
+/// let err = LengthError { expected: vec![0; 4], buf: vec![0; 3] };
+/// assert_eq!(err.to_string(), "expected 4 bytes, got 3");
+/// ```
+///
+/// ## Alternate messages
+///
+/// `alt = "..."` gives a second template that is used when the error is
+/// formatted with `{:#}`, as `error-stack` does for its verbose output. It can
+/// use the same placeholders and extra arguments as the main template. Without
+/// it, `{:#}` prints the normal message. An `{alt}` placeholder is rejected
+/// next to an alternate template, so give such an argument another name, or
+/// write `{r#alt}` to refer to a field named `alt`.
+///
+/// ```
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Debug, Error)]
+/// #[display("request failed", alt = "request to {host} failed")]
+/// struct RequestError {
+///     host: String,
+/// }
+///
+/// let err = RequestError { host: "example.com".to_string() };
+/// assert_eq!(format!("{err}"), "request failed");
+/// assert_eq!(format!("{err:#}"), "request to example.com failed");
+/// ```
+///
+/// ## Width, fill and precision
+///
+/// The generated `Display` implementation honors the width, fill, alignment
+/// and precision of the caller, so errors can be lined up in tables. Messages
+/// without placeholders are written with [`Formatter::pad`]. Other messages
+/// are formatted once into a 256-byte buffer on the stack when a width or
+/// precision is given, and then padded. A longer message is written as it is
+/// formatted, so it is cut off at the precision, but any fill goes after it.
+/// `#[error(no_padding)]` turns this off and writes every message as is.
+///
+/// ```
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Debug, Error)]
+/// #[display("{0} rows rejected")]
+/// struct ImportError(usize);
+///
+/// assert_eq!(format!("{:>18}", ImportError(3)), "   3 rows rejected");
+/// assert_eq!(format!("{:.6}", ImportError(3)), "3 rows");
+/// ```
+///
+/// [`Formatter::pad`]: core::fmt::Formatter::pad
+///
+/// ## Static messages
+///
+/// Messages without placeholders are written with [`Formatter::pad`] instead
+/// of `write!`, or with a single `write_str` call under `no_padding`.
+///
+/// `#[error(message_fn)]` also generates an inherent `const fn message(&self)`,
+/// which returns the message without formatting it, for use in `const` items
+/// or metrics labels. It returns `&'static str` if no message has
+/// placeholders, and `Option<&'static str>` otherwise, with `None` for the
+/// messages that are formatted at runtime. The method has the visibility of
+/// the type, so it becomes part of the type's public API, and its return type
+/// changes when a message gains a placeholder. It is opt-in so that deriving
+/// `Error` does not add it to the API unasked, and so that it does not clash
+/// with a `message` method the type already defines.
+///
+/// ```
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Debug, Error)]
+/// #[error(message_fn)]
+/// #[display("config is invalid")]
+/// struct ConfigError;
+///
+/// const LABEL: &str = ConfigError.message();
+/// assert_eq!(LABEL, "config is invalid");
+/// ```
+///
+/// ## Strict mode
+///
+/// [`error-stack`] recommends attaching data to the [`Report`] instead of
+/// formatting it into the context's message. `#[error(strict)]` turns every
+/// placeholder that formats a field or an extra argument into a compile error.
+/// Intrinsic placeholders are still allowed. `strict = "warn"` reports them as
+/// deprecation warnings instead, to migrate gradually, and `strict = "allow"`
+/// turns the check off.
+///
+/// ```compile_fail
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Debug, Error)]
+/// #[display("invalid card string: {0:?}")]
+/// #[error(strict)]
+/// struct ParseCardError(String);
+/// ```
+///
+/// The check is off on types without a `strict` key, so every type opts in
+/// on its own and a dependency can never turn it on for your types.
+///
+/// ## Message style
+///
+/// The [Rust API guidelines] ask for error messages that are lowercase and
+/// have no trailing punctuation. `#[error(style = "warn")]` or
+/// `style = "deny"` checks every `display` string against them. A message
+/// must not be empty or have leading or trailing whitespace, start with a
+/// capital letter (acronyms like `HTTP` and placeholders are fine) or end with
+/// a period. Messages from doc comments are not checked. Like strict mode, the
+/// check is off on types without a `style` key.
+///
+/// ```compile_fail
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Debug, Error)]
+/// #[display("Failed to parse config.")]
+/// #[error(style = "deny")]
+/// struct ParseConfigError;
+/// ```
+///
+/// ## Wrapping variant messages
+///
+/// `#[error(wrap = "...")]` on an enum writes the message of every variant,
+/// including the enum's own `display` and `transparent` variants, into a
+/// common template, in place of its `{message}` placeholder. Besides
+/// `{message}`, the template can only use `{type}`, `{variant}` and
+/// `{module}`, and placeholders take format specs as in `display` templates.
+/// A width or precision on `{message}` only applies to messages without
+/// placeholders, since the others are passed as
+/// [`format_args!`](core::format_args), which ignores them.
+/// `#[error(prefix = "...")]` is the short form for a template that ends
+/// with the message. Each variant is still formatted with a single `write!`,
+/// without an intermediate `String`.
+///
+/// ```
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Debug, Error)]
+/// #[error(prefix = "storage: ")]
+/// #[display("unknown failure")]
+/// enum StorageError {
+///     #[display("file {0} not found")]
+///     NotFound(String),
+///
+///     Other,
+/// }
+///
+/// assert_eq!(
+///     StorageError::NotFound(String::from("a.txt")).to_string(),
+///     "storage: file a.txt not found"
+/// );
+/// assert_eq!(StorageError::Other.to_string(), "storage: unknown failure");
+/// ```
+///
+/// ## Debug implementation
+///
+/// [`Error`](core::error::Error) requires [`Debug`](core::fmt::Debug).
+/// `#[error(debug = "derive")]` implements it field by field, like
+/// `#[derive(Debug)]`, and `#[error(debug = "display")]` forwards it to the
+/// message. Without either, a type that does not implement `Debug` is
+/// reported at its name. Types with type parameters are not checked, since
+/// their `Debug` implementation can have any bounds: their [`Error`]
+/// implementation requires `Self: Debug` instead, so it exists exactly where
+/// the `Debug` implementation does.
+///
+/// ```
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Error)]
+/// #[error(debug = "display")]
+/// #[display("invalid card string")]
+/// struct ParseCardError;
+///
+/// assert_eq!(format!("{ParseCardError:?}"), "invalid card string");
+/// ```
+///
+/// ```compile_fail
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Error)]
+/// #[display("invalid card string")]
+/// struct ParseCardError;
+/// ```
+///
+/// ## Namespaced attribute
+///
+/// If another derive macro on the same type also uses a `display` attribute,
+/// all options can be written inside a single `error_stack` attribute instead.
+/// `display = "..."` (or `display("...", args...)`) and the `display` modes
+/// (`transparent`, `auto`, `doc`) sit next to the keys of the `error`
+/// attribute.
+///
+/// ```
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Debug, Error)]
+/// #[error_stack(display = "storage error", no_context_check)]
+/// enum StorageError {
+///     #[error_stack(display("file {0} not found"))]
+///     NotFound(String),
+///
+///     #[error_stack(transparent)]
+///     Io(std::io::Error),
+///
+///     Other,
+/// }
+///
+/// assert_eq!(StorageError::Other.to_string(), "storage error");
+/// ```
+///
+/// ## Platform-specific messages
+///
+/// `display` attributes can be wrapped in `cfg_attr` to change the message for
+/// some targets or features. The compiler expands `cfg_attr` before the derive
+/// runs, so the predicates must be mutually exclusive: two active `display`
+/// attributes are rejected as duplicates. Variants without an active one fall
+/// back to the enum's default message, and every configuration must still end
+/// up with a message.
+///
+/// ```
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Debug, Error)]
+/// #[cfg_attr(windows, display("named pipe closed"))]
+/// #[cfg_attr(not(windows), display("unix socket closed"))]
+/// struct ChannelClosedError;
+///
+/// let message = ChannelClosedError.to_string();
+/// assert!(message.ends_with("closed"));
+/// ```
+///
+/// ## Custom trait bounds
+///
+/// The `error` attribute overrides the trait bounds inferred for generic
+/// types. On the type, `bound = "..."` replaces all generated `where`
+/// predicates (including the `Self: Debug` bound on the [`Error`]
+/// implementation), and `add_bound = "..."` adds predicates to the inferred
+/// ones. On a type parameter, the same keys take the bounds for that parameter
+/// only.
+///
+/// ```
+/// use std::fmt::{self, Debug, Display, Formatter};
+///
+/// use error_stack_macros2::Error;
+///
+/// #[derive(Error)]
+/// #[display("invalid value: {value}")]
+/// #[error(bound = "T: Display")]
+/// struct InvalidValueError<#[error(add_bound = "Send + Sync")] T> {
+///     value: T,
+/// }
+///
+/// impl<T> Debug for InvalidValueError<T> {
+///     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+///         f.write_str("InvalidValueError")
+///     }
+/// }
+///
+/// let err = InvalidValueError { value: 5 };
+/// assert_eq!(err.to_string(), "invalid value: 5");
+/// ```
+///
 /// # This may look familiar...
 ///
 /// This derive macro is heavily inspired by the popular [`thiserror`] crate. In
@@ -177,18 +604,23 @@ use types::ErrorStackDeriveInput;
 /// best practices, whereas [`thiserror`] is more flexible and designed for
 /// general use cases.
 ///
-/// Also, due to this macro's more simple and restricted design, it can
-/// potentially be more efficient than [`thiserror`] in terms of compile time
-/// and generated code size.
+/// For example, there is no `#[source]`, `#[from]` or `#[backtrace]`: the
+/// [`Error`] implementation never returns a source, since [`error-stack`]
+/// keeps the chain of contexts and the backtrace in the [`Report`] instead.
 ///
 /// [`Error`]: core::error::Error
+/// [`module_path!()`]: core::module_path
 /// [`error-stack`]: https://crates.io/crates/error-stack
 /// [`Report`]: https://docs.rs/error-stack/latest/error_stack/struct.Report.html
 /// [`Display`]: core::fmt::Display
+/// [`Debug`]: core::fmt::Debug
+/// [`Infallible`]: core::convert::Infallible
+/// [`format!`]: std::format
 /// [`thiserror`]: https://crates.io/crates/thiserror
+/// [Rust API guidelines]: https://rust-lang.github.io/api-guidelines/interoperability.html#error-types-are-meaningful-and-well-behaved-c-good-err
 // TODO: remove #[inline] when fixed
 #[inline]
-#[proc_macro_derive(Error, attributes(display))]
+#[proc_macro_derive(Error, attributes(display, error, error_stack))]
 pub fn impl_error_stack(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as ErrorStackDeriveInput);
     quote! { #derive_input }.into()
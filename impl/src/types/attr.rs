@@ -0,0 +1,495 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{ToTokens as _, quote};
+use syn::{
+    Attribute, Data, DeriveInput, Field, GenericParam, LitStr, Meta, Path,
+    Token, TypeParamBound, WherePredicate, meta::ParseNestedMeta,
+    parenthesized, parse_quote_spanned, punctuated::Punctuated,
+    spanned::Spanned as _, token::Paren,
+};
+
+use super::{debug::DebugMode, fmt::Wrapper, lint::Level, util};
+
+/// Replaces every `#[error_stack(...)]` attribute in the input with the
+/// equivalent `#[display(...)]` and `#[error(...)]` attributes, so that the
+/// rest of the macro only has to handle the short forms.
+pub(crate) fn expand_namespaced_attrs(
+    derive_input: &mut DeriveInput,
+) -> syn::Result<()> {
+    let mut attr_lists = vec![&mut derive_input.attrs];
+
+    for param in &mut derive_input.generics.params {
+        attr_lists.push(match *param {
+            GenericParam::Type(ref mut type_p) => &mut type_p.attrs,
+            GenericParam::Lifetime(ref mut lifetime_p) => &mut lifetime_p.attrs,
+            GenericParam::Const(ref mut const_p) => &mut const_p.attrs,
+        });
+    }
+
+    match derive_input.data {
+        Data::Struct(ref mut data) => {
+            attr_lists
+                .extend(data.fields.iter_mut().map(|field| &mut field.attrs));
+        }
+        Data::Enum(ref mut data) => {
+            for variant in &mut data.variants {
+                attr_lists.push(&mut variant.attrs);
+                attr_lists.extend(
+                    variant.fields.iter_mut().map(|field| &mut field.attrs),
+                );
+            }
+        }
+        Data::Union(_) => {}
+    }
+
+    let errors = attr_lists
+        .into_iter()
+        .filter_map(|attrs| expand_namespaced(attrs).err());
+
+    match errors.reduce(|mut err, err2| {
+        err.combine(err2);
+        err
+    }) {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Rejects `display` attributes on generic parameters and fields, which would
+/// otherwise be accepted and ignored because `display` is a helper attribute.
+pub(crate) fn reject_stray_display_attrs(
+    derive_input: &DeriveInput,
+) -> syn::Result<()> {
+    let param_attrs =
+        derive_input
+            .generics
+            .params
+            .iter()
+            .flat_map(|param| match *param {
+                GenericParam::Type(ref type_p) => &type_p.attrs,
+                GenericParam::Lifetime(ref lifetime_p) => &lifetime_p.attrs,
+                GenericParam::Const(ref const_p) => &const_p.attrs,
+            });
+
+    let fields: Vec<&Field> = match derive_input.data {
+        Data::Struct(ref data) => data.fields.iter().collect(),
+        Data::Enum(ref data) => data
+            .variants
+            .iter()
+            .flat_map(|variant| &variant.fields)
+            .collect(),
+        Data::Union(_) => Vec::new(),
+    };
+
+    let param_errors = param_attrs
+        .filter(|attr| attr.path().is_ident("display"))
+        .map(|attr| {
+            syn::Error::new_spanned(
+                attr,
+                "`display` attributes are not supported on generic parameters",
+            )
+        });
+
+    let field_errors = fields
+        .into_iter()
+        .flat_map(|field| &field.attrs)
+        .filter(|attr| attr.path().is_ident("display"))
+        .map(|attr| {
+            syn::Error::new_spanned(
+                attr,
+                "`display` attributes are not supported on fields\nmove it to the struct or enum variant",
+            )
+        });
+
+    match param_errors.chain(field_errors).reduce(|mut err, err2| {
+        err.combine(err2);
+        err
+    }) {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+fn expand_namespaced(attrs: &mut Vec<Attribute>) -> syn::Result<()> {
+    if !attrs.iter().any(|attr| attr.path().is_ident("error_stack")) {
+        return Ok(());
+    }
+
+    let mut expanded_attrs = Vec::with_capacity(attrs.len());
+    for attr in attrs.drain(..) {
+        if !attr.path().is_ident("error_stack") {
+            expanded_attrs.push(attr);
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            let Some(key) = meta.path.get_ident() else {
+                return Err(meta.error(UNKNOWN_KEY_MESSAGE));
+            };
+            let span = key.span();
+
+            match key.to_string().as_str() {
+                "display" => {
+                    let tokens: TokenStream2 = if meta.input.peek(Token![=]) {
+                        let lit_str: LitStr = meta.value()?.parse()?;
+                        quote! { #lit_str }
+                    } else {
+                        let content;
+                        parenthesized!(content in meta.input);
+                        content.parse()?
+                    };
+
+                    expanded_attrs.push(
+                        parse_quote_spanned! {span=> #[display(#tokens)] },
+                    );
+                }
+
+                "transparent" | "doc" => {
+                    expanded_attrs
+                        .push(parse_quote_spanned! {span=> #[display(#key)] });
+                }
+
+                "auto" => {
+                    let options: TokenStream2 = if meta.input.peek(Paren) {
+                        let content;
+                        parenthesized!(content in meta.input);
+                        content.parse()?
+                    } else {
+                        TokenStream2::new()
+                    };
+
+                    expanded_attrs.push(parse_quote_spanned! {span=>
+                        #[display(#key, #options)]
+                    });
+                }
+
+                "bound" | "add_bound" | "style" | "wrap" | "prefix"
+                | "debug" => {
+                    let lit_str: LitStr = meta.value()?.parse()?;
+                    expanded_attrs.push(parse_quote_spanned! {span=>
+                        #[error(#key = #lit_str)]
+                    });
+                }
+
+                "strict" => {
+                    if meta.input.peek(Token![=]) {
+                        let lit_str: LitStr = meta.value()?.parse()?;
+                        expanded_attrs.push(parse_quote_spanned! {span=>
+                            #[error(#key = #lit_str)]
+                        });
+                    } else {
+                        expanded_attrs.push(
+                            parse_quote_spanned! {span=> #[error(#key)] },
+                        );
+                    }
+                }
+
+                "no_context_check" | "no_padding" | "message_fn"
+                | "uninhabited" => {
+                    expanded_attrs
+                        .push(parse_quote_spanned! {span=> #[error(#key)] });
+                }
+
+                "forward_attrs" => {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let paths: TokenStream2 = content.parse()?;
+                    expanded_attrs.push(parse_quote_spanned! {span=>
+                        #[error(#key(#paths))]
+                    });
+                }
+
+                _ => return Err(meta.error(UNKNOWN_KEY_MESSAGE)),
+            }
+
+            Ok(())
+        })?;
+    }
+
+    *attrs = expanded_attrs;
+    Ok(())
+}
+
+const UNKNOWN_KEY_MESSAGE: &str = "unknown `error_stack` attribute key\nexpected `display`, `transparent`, `auto`, `doc`, `bound`, `add_bound`, `no_context_check`, `no_padding`, `message_fn`, `forward_attrs`, `strict`, `style`, `wrap`, `prefix`, `debug` or `uninhabited`";
+
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "each flag is a separate key of the `error` attribute"
+)]
+pub(crate) struct ContainerAttr {
+    bounds: BoundAttr<WherePredicate>,
+    no_context_check: bool,
+    no_padding: bool,
+    message_fn: bool,
+    forward_attrs: ForwardAttrs,
+    strict: Option<Level>,
+    style: Option<Level>,
+    wrapper: Option<Wrapper>,
+    debug: Option<DebugMode>,
+}
+
+impl ContainerAttr {
+    pub(crate) fn take(attrs: &mut Vec<Attribute>) -> syn::Result<Self> {
+        let mut container_attr = Self {
+            bounds: BoundAttr::default(),
+            no_context_check: false,
+            no_padding: false,
+            message_fn: false,
+            forward_attrs: ForwardAttrs::default(),
+            strict: None,
+            style: None,
+            wrapper: None,
+            debug: None,
+        };
+
+        for attr in util::take_error_attrs(attrs) {
+            attr.parse_nested_meta(|meta| {
+                if container_attr.bounds.parse_key(&meta, |lit_str| {
+                    lit_str.parse_with(
+                        Punctuated::<WherePredicate, Token![,]>::parse_terminated,
+                    )
+                })? {
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("no_context_check") {
+                    container_attr.no_context_check = true;
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("no_padding") {
+                    container_attr.no_padding = true;
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("message_fn") {
+                    container_attr.message_fn = true;
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("forward_attrs") {
+                    return meta.parse_nested_meta(|path_meta| {
+                        container_attr.forward_attrs.extra.push(path_meta.path);
+                        Ok(())
+                    });
+                }
+
+                if meta.path.is_ident("strict") {
+                    if container_attr.strict.is_some() {
+                        return Err(meta.error("duplicate `strict` key"));
+                    }
+
+                    container_attr.strict = Some(if meta.input.peek(Token![=]) {
+                        Level::from_lit_str(&meta.value()?.parse()?)?
+                    } else {
+                        Level::Deny
+                    });
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("style") {
+                    if container_attr.style.is_some() {
+                        return Err(meta.error("duplicate `style` key"));
+                    }
+
+                    container_attr.style =
+                        Some(Level::from_lit_str(&meta.value()?.parse()?)?);
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("wrap") || meta.path.is_ident("prefix") {
+                    if container_attr.wrapper.is_some() {
+                        return Err(meta.error(
+                            "only one of `wrap` and `prefix` can be given",
+                        ));
+                    }
+
+                    let lit_str: LitStr = meta.value()?.parse()?;
+                    container_attr.wrapper = Some(if meta.path.is_ident("wrap") {
+                        Wrapper::from_template(lit_str)?
+                    } else {
+                        Wrapper::from_prefix(lit_str)
+                    });
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("debug") {
+                    if container_attr.debug.is_some() {
+                        return Err(meta.error("duplicate `debug` key"));
+                    }
+
+                    container_attr.debug =
+                        Some(DebugMode::from_lit_str(&meta.value()?.parse()?)?);
+                    return Ok(());
+                }
+
+                Err(meta.error(
+                    "unknown `error` attribute key\nexpected `bound`, `add_bound`, `no_context_check`, `no_padding`, `message_fn`, `forward_attrs`, `strict`, `style`, `wrap`, `prefix` or `debug`",
+                ))
+            })?;
+        }
+
+        Ok(container_attr)
+    }
+
+    pub(crate) const fn no_context_check(&self) -> bool {
+        self.no_context_check
+    }
+
+    pub(crate) const fn no_padding(&self) -> bool {
+        self.no_padding
+    }
+
+    pub(crate) const fn message_fn(&self) -> bool {
+        self.message_fn
+    }
+
+    pub(crate) const fn debug(&self) -> Option<DebugMode> {
+        self.debug
+    }
+
+    pub(crate) const fn strict(&self) -> Option<Level> {
+        self.strict
+    }
+
+    pub(crate) const fn style(&self) -> Option<Level> {
+        self.style
+    }
+
+    pub(crate) fn into_parts(
+        self,
+    ) -> (BoundAttr<WherePredicate>, ForwardAttrs, Option<Wrapper>) {
+        (self.bounds, self.forward_attrs, self.wrapper)
+    }
+}
+
+/// Decides which attributes of the input are copied onto the generated code.
+/// Only `cfg`, `cfg_attr` and lint attributes are forwarded by default, plus
+/// the ones listed in `#[error(forward_attrs(...))]`.
+#[derive(Default)]
+pub(crate) struct ForwardAttrs {
+    extra: Vec<Path>,
+}
+
+impl ForwardAttrs {
+    const DEFAULT: [&str; 6] =
+        ["cfg", "cfg_attr", "allow", "expect", "warn", "deny"];
+
+    pub(crate) fn filter(&self, attrs: Vec<Attribute>) -> Vec<Attribute> {
+        attrs
+            .into_iter()
+            .filter(|attr| {
+                let path = attr.path();
+                Self::DEFAULT.iter().any(|name| path.is_ident(name))
+                    || self.extra.iter().any(|extra| {
+                        extra.to_token_stream().to_string()
+                            == path.to_token_stream().to_string()
+                    })
+            })
+            .map(|mut attr| {
+                // an `expect` would be unfulfilled on code where the lint
+                // never fires, so it is forwarded as an `allow`
+                if let Meta::List(ref mut list) = attr.meta
+                    && list.path.is_ident("expect")
+                {
+                    let span = list.path.span();
+                    list.path = parse_quote_spanned! {span=> allow };
+                }
+
+                attr
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn is_marked_uninhabited(attrs: &[Attribute]) -> syn::Result<bool> {
+    let mut is_uninhabited = false;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("error")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("uninhabited") {
+                is_uninhabited = true;
+                return Ok(());
+            }
+
+            Err(meta
+                .error("unknown `error` attribute key\nexpected `uninhabited`"))
+        })?;
+    }
+
+    Ok(is_uninhabited)
+}
+
+pub(crate) struct BoundAttr<T> {
+    bound: Option<Vec<T>>,
+    add_bound: Vec<T>,
+}
+
+impl<T> Default for BoundAttr<T> {
+    fn default() -> Self {
+        Self {
+            bound: None,
+            add_bound: Vec::new(),
+        }
+    }
+}
+
+impl BoundAttr<TypeParamBound> {
+    pub(crate) fn from_param_attrs(attrs: Vec<Attribute>) -> syn::Result<Self> {
+        let mut bound_attr = Self::default();
+
+        for attr in attrs {
+            attr.parse_nested_meta(|meta| {
+                if bound_attr.parse_key(&meta, |lit_str| {
+                    lit_str.parse_with(
+                        Punctuated::<TypeParamBound, Token![+]>::parse_terminated,
+                    )
+                })? {
+                    return Ok(());
+                }
+
+                Err(meta.error(
+                    "unknown `error` attribute key\nexpected `bound` or `add_bound`",
+                ))
+            })?;
+        }
+
+        Ok(bound_attr)
+    }
+}
+
+impl<T> BoundAttr<T> {
+    pub(crate) fn bound(&self) -> Option<&[T]> {
+        self.bound.as_deref()
+    }
+
+    pub(crate) fn add_bound(&self) -> &[T] {
+        &self.add_bound
+    }
+
+    fn parse_key<S, P>(
+        &mut self,
+        meta: &ParseNestedMeta<'_>,
+        parser: P,
+    ) -> syn::Result<bool>
+    where
+        P: Fn(&LitStr) -> syn::Result<Punctuated<T, S>>,
+    {
+        if meta.path.is_ident("bound") {
+            if self.bound.is_some() {
+                return Err(meta.error("duplicate `bound` key"));
+            }
+
+            let lit_str: LitStr = meta.value()?.parse()?;
+            self.bound = Some(parser(&lit_str)?.into_iter().collect());
+            return Ok(true);
+        }
+
+        if meta.path.is_ident("add_bound") {
+            let lit_str: LitStr = meta.value()?.parse()?;
+            self.add_bound.extend(parser(&lit_str)?);
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashSet;
+
+use quote::{ToTokens as _, quote};
+use syn::{GenericParam, Generics, Ident, TypeParamBound, WherePredicate};
+
+use super::{attr::BoundAttr, fmt::FieldBound, util};
+
+pub(crate) struct Bounds {
+    type_params: Vec<Ident>,
+    container: BoundAttr<WherePredicate>,
+    params: Vec<ParamBounds>,
+}
+
+struct ParamBounds {
+    ident: Ident,
+    attr: BoundAttr<TypeParamBound>,
+}
+
+impl Bounds {
+    pub(crate) fn take(
+        container: BoundAttr<WherePredicate>,
+        generics: &mut Generics,
+    ) -> syn::Result<Self> {
+        let mut params = Vec::new();
+        let mut errors = Vec::new();
+
+        for param in &mut generics.params {
+            let (param_attrs, ident) = match *param {
+                GenericParam::Type(ref mut type_p) => {
+                    (&mut type_p.attrs, Some(&type_p.ident))
+                }
+                GenericParam::Lifetime(ref mut lifetime_p) => {
+                    (&mut lifetime_p.attrs, None)
+                }
+                GenericParam::Const(ref mut const_p) => {
+                    (&mut const_p.attrs, None)
+                }
+            };
+
+            let error_attrs = util::take_error_attrs(param_attrs);
+            let Some(type_ident) = ident else {
+                errors.extend(error_attrs.iter().map(|attr| {
+                    syn::Error::new_spanned(
+                        attr,
+                        "`error` attributes are only supported on type parameters",
+                    )
+                }));
+                continue;
+            };
+
+            if error_attrs.is_empty() {
+                continue;
+            }
+
+            match BoundAttr::from_param_attrs(error_attrs) {
+                Ok(attr) => params.push(ParamBounds {
+                    ident: type_ident.clone(),
+                    attr,
+                }),
+                Err(err) => errors.push(err),
+            }
+        }
+
+        if let Some(err) = errors.into_iter().reduce(|mut err, err2| {
+            err.combine(err2);
+            err
+        }) {
+            return Err(err);
+        }
+
+        let type_params = generics
+            .type_params()
+            .map(|param| param.ident.clone())
+            .collect();
+
+        Ok(Self {
+            type_params,
+            container,
+            params,
+        })
+    }
+
+    pub(crate) fn predicates(
+        &self,
+        field_bounds: Vec<&FieldBound>,
+        is_error_impl: bool,
+    ) -> Vec<WherePredicate> {
+        let type_params: Vec<&Ident> = self.type_params.iter().collect();
+        let replaced_params: Vec<&Ident> = self
+            .params
+            .iter()
+            .filter(|param| param.attr.bound().is_some())
+            .map(|param| &param.ident)
+            .collect();
+
+        let mut predicates = Vec::new();
+
+        if let Some(bound) = self.container.bound() {
+            predicates.extend(bound.iter().cloned());
+        } else {
+            predicates.extend(
+                field_bounds
+                    .into_iter()
+                    .filter(|bound| {
+                        bound.mentions_any(&type_params)
+                            && !bound.mentions_any(&replaced_params)
+                    })
+                    .map(FieldBound::to_predicate),
+            );
+
+            if is_error_impl && !type_params.is_empty() {
+                predicates.push(util::self_debug_predicate());
+            }
+        }
+
+        for param in &self.params {
+            let ident = &param.ident;
+            let param_bounds = param
+                .attr
+                .bound()
+                .into_iter()
+                .chain([param.attr.add_bound()]);
+
+            for bounds in param_bounds {
+                if bounds.is_empty() {
+                    continue;
+                }
+
+                #[expect(
+                    clippy::unwrap_used,
+                    reason = "this `TokenStream` is valid"
+                )]
+                predicates.push(
+                    syn::parse2(quote! { #ident: #(#bounds)+* }).unwrap(),
+                );
+            }
+        }
+
+        predicates.extend(self.container.add_bound().iter().cloned());
+
+        let mut seen_predicates = HashSet::new();
+        predicates.retain(|predicate| {
+            seen_predicates.insert(predicate.to_token_stream().to_string())
+        });
+
+        predicates
+    }
+}
@@ -0,0 +1,116 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{ToTokens as _, quote, quote_spanned};
+use syn::{
+    Attribute, Data, Fields, Generics, Ident, Type, spanned::Spanned as _,
+};
+
+use super::util;
+
+pub(crate) struct ContextAssertion {
+    fields: Vec<CheckedField>,
+}
+
+struct CheckedField {
+    cfg_attrs: Vec<Attribute>,
+    ty: Type,
+}
+
+impl ContextAssertion {
+    pub(crate) fn new(data: &Data) -> Self {
+        let mut fields = Vec::new();
+
+        match *data {
+            Data::Struct(ref data_struct) => {
+                push_fields(&mut fields, &[], &data_struct.fields);
+            }
+
+            Data::Enum(ref data_enum) => {
+                for variant in &data_enum.variants {
+                    let cfg_attrs = util::cfg_attrs(&variant.attrs);
+                    push_fields(&mut fields, &cfg_attrs, &variant.fields);
+                }
+            }
+
+            Data::Union(_) => {}
+        }
+
+        Self { fields }
+    }
+
+    pub(crate) fn disabled() -> Self {
+        Self { fields: Vec::new() }
+    }
+
+    pub(crate) fn expand(&self, generics: &Generics) -> TokenStream2 {
+        let self_ident = Ident::new("Self", Span::call_site());
+        let mut excluded_idents: Vec<&Ident> =
+            generics.type_params().map(|param| &param.ident).collect();
+        excluded_idents.push(&self_ident);
+
+        let assertions: Vec<TokenStream2> = self
+            .fields
+            .iter()
+            .filter(|field| {
+                !util::tokens_mention_any(
+                    field.ty.to_token_stream(),
+                    &excluded_idents,
+                )
+            })
+            .map(|field| {
+                let CheckedField {
+                    ref cfg_attrs,
+                    ref ty,
+                } = *field;
+                let span = Span::call_site().located_at(ty.span());
+
+                quote_spanned! {span=>
+                    #(#cfg_attrs)*
+                    {
+                        context_must_be_send::<#ty>();
+                        context_must_be_sync::<#ty>();
+                        context_must_be_static::<#ty>();
+                    }
+                }
+            })
+            .collect();
+
+        if assertions.is_empty() {
+            return TokenStream2::new();
+        }
+
+        let where_clause = &generics.where_clause;
+
+        quote! {
+            #[allow(dead_code, single_use_lifetimes)]
+            const _: () = {
+                fn context_must_be_send<T: ?::core::marker::Sized + ::core::marker::Send>() {}
+                fn context_must_be_sync<T: ?::core::marker::Sized + ::core::marker::Sync>() {}
+                fn context_must_be_static<T: ?::core::marker::Sized + 'static>() {}
+
+                fn assert_context_fields #generics () #where_clause {
+                    #(#assertions)*
+                }
+            };
+        }
+    }
+}
+
+fn push_fields(
+    checked_fields: &mut Vec<CheckedField>,
+    variant_cfg_attrs: &[Attribute],
+    fields: &Fields,
+) {
+    checked_fields.extend(
+        fields
+            .iter()
+            .filter(|field| !matches!(field.ty, Type::Never(_)))
+            .map(|field| CheckedField {
+                cfg_attrs: variant_cfg_attrs
+                    .iter()
+                    .cloned()
+                    .chain(util::cfg_attrs(&field.attrs))
+                    .collect(),
+                ty: field.ty.clone(),
+            }),
+    );
+}
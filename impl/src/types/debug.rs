@@ -0,0 +1,300 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    Attribute, Data, Fields, Generics, Ident, Index, LitStr, Member,
+    WherePredicate, ext::IdentExt as _, parse_quote, parse_quote_spanned,
+};
+
+use super::util;
+
+/// The `Debug` implementation that the macro provides, from
+/// `#[error(debug = "...")]`.
+#[derive(Clone, Copy)]
+pub(crate) enum DebugMode {
+    Derive,
+    Display,
+}
+
+impl DebugMode {
+    pub(crate) fn from_lit_str(lit_str: &LitStr) -> syn::Result<Self> {
+        match lit_str.value().as_str() {
+            "derive" => Ok(Self::Derive),
+            "display" => Ok(Self::Display),
+            _ => Err(syn::Error::new(
+                lit_str.span(),
+                "unknown `debug` mode\nexpected `derive` or `display`",
+            )),
+        }
+    }
+}
+
+/// Where the `Debug` implementation that `Error` requires comes from.
+pub(crate) enum DebugImpl {
+    /// The user implements `Debug`. A missing implementation is reported at
+    /// the type name.
+    User(Ident),
+
+    /// A field-wise implementation, like the one of `#[derive(Debug)]`.
+    Derive(Data),
+
+    /// An implementation that forwards to `Display`.
+    Display,
+}
+
+impl DebugImpl {
+    pub(crate) fn new(
+        mode: Option<DebugMode>,
+        data: &Data,
+        ident: &Ident,
+    ) -> Self {
+        match mode {
+            None => Self::User(ident.clone()),
+            Some(DebugMode::Derive) => Self::Derive(data.clone()),
+            Some(DebugMode::Display) => Self::Display,
+        }
+    }
+
+    /// Returns the bound of the `Error` implementation on the user's `Debug`
+    /// implementation, for types without type parameters, which get one from
+    /// [`Bounds`](super::bounds::Bounds) instead. With the bound, a missing
+    /// implementation is only reported by [`expand_check`](Self::expand_check),
+    /// at the type name. Bounds that mention no generics at all are rejected
+    /// when they do not hold, unless they are higher-ranked.
+    pub(crate) fn error_predicate(
+        &self,
+        generics: &Generics,
+    ) -> Option<WherePredicate> {
+        let Self::User(ref ident) = *self else {
+            return None;
+        };
+        let span = ident.span();
+
+        if generics.type_params().next().is_some() {
+            return None;
+        }
+
+        Some(if generics.params.is_empty() {
+            parse_quote_spanned! {span=>
+                for<'__error_stack> Self: ::core::fmt::Debug
+            }
+        } else {
+            parse_quote_spanned! {span=> Self: ::core::fmt::Debug }
+        })
+    }
+
+    /// Returns the bounds of the generated `Debug` implementation. Forwarding
+    /// to `Display` needs the same bounds as the `Display` implementation,
+    /// while the field-wise one needs every type parameter to implement
+    /// `Debug`.
+    pub(crate) fn predicates(
+        &self,
+        generics: &Generics,
+        display_predicates: Vec<WherePredicate>,
+    ) -> Vec<WherePredicate> {
+        match *self {
+            Self::User(..) => Vec::new(),
+            Self::Derive(_) => {
+                drop(display_predicates);
+                generics
+                    .type_params()
+                    .map(|param| {
+                        let ident = &param.ident;
+                        parse_quote! { #ident: ::core::fmt::Debug }
+                    })
+                    .collect()
+            }
+            Self::Display => display_predicates,
+        }
+    }
+
+    /// Expands to the body of the generated `Debug::fmt`, if there is one.
+    pub(crate) fn expand_body(&self, ident: &Ident) -> Option<TokenStream2> {
+        match *self {
+            Self::User(..) => None,
+            Self::Derive(ref data) => Some(expand_derived(data, ident)),
+            Self::Display => Some(quote! {
+                ::core::fmt::Display::fmt(self, f)
+            }),
+        }
+    }
+
+    /// Expands to a check that the type implements `Debug`, which fails with
+    /// an error at the type name. Types with type parameters are not checked,
+    /// since their `Debug` implementation may have any bounds: the `Error`
+    /// implementation requires `Self: Debug` instead.
+    pub(crate) fn expand_check(&self, generics: &Generics) -> TokenStream2 {
+        let Self::User(ref ident) = *self else {
+            return TokenStream2::new();
+        };
+
+        if generics.type_params().next().is_some() {
+            return TokenStream2::new();
+        }
+
+        let (_, type_generics, where_clause) = generics.split_for_impl();
+        let self_ty = quote_spanned! {ident.span()=>
+            AssertContextDebug<#ident #type_generics>
+        };
+
+        // The check is the well-formedness of a type in the header of a trait
+        // impl rather than a call in a function, since an unused function
+        // would make the type and all of its fields live for the dead code
+        // lint.
+        quote! {
+            #[allow(single_use_lifetimes)]
+            const _: () = {
+                #[allow(dead_code)]
+                #[diagnostic::on_unimplemented(
+                    message = "`{Self}` must implement `Debug` to derive `Error`",
+                    label = "`Debug` is not implemented for this type",
+                    note = "add `#[derive(Debug)]`, or let `Error` implement it with `#[error(debug = \"derive\")]` or `#[error(debug = \"display\")]`"
+                )]
+                trait ContextDebug {}
+
+                #[diagnostic::do_not_recommend]
+                impl<T: ?::core::marker::Sized + ::core::fmt::Debug> ContextDebug for T {}
+
+                #[allow(dead_code)]
+                struct AssertContextDebug<T: ?::core::marker::Sized + ContextDebug>(
+                    ::core::marker::PhantomData<T>,
+                );
+
+                impl #generics ::core::marker::Unpin for #self_ty #where_clause {}
+            };
+        }
+    }
+}
+
+fn expand_derived(data: &Data, ident: &Ident) -> TokenStream2 {
+    match *data {
+        Data::Struct(ref data_struct) => {
+            let fields =
+                data_struct.fields.iter().zip(0..).map(|(field, index)| {
+                    let member = field.ident.clone().map_or_else(
+                        || {
+                            Member::Unnamed(Index {
+                                index,
+                                span: Span::call_site(),
+                            })
+                        },
+                        Member::Named,
+                    );
+
+                    DebugField {
+                        cfg_attrs: util::cfg_attrs(&field.attrs),
+                        name: field.ident.as_ref().map(ident_name),
+                        value: quote! { &self.#member },
+                    }
+                });
+
+            expand_fields(&ident_name(ident), &data_struct.fields, fields)
+        }
+
+        Data::Enum(ref data_enum) => {
+            let arms = data_enum.variants.iter().map(|variant| {
+                let variant_ident = &variant.ident;
+                let variant_cfg_attrs = util::cfg_attrs(&variant.attrs);
+                let bindings: Vec<Ident> = (0..variant.fields.len())
+                    .map(|index| format_ident!("__self_{}", index))
+                    .collect();
+
+                let patterns =
+                    variant.fields.iter().zip(&bindings).zip(0..).map(
+                        |((field, binding), index)| {
+                            let field_cfg_attrs = util::cfg_attrs(&field.attrs);
+                            let member = field.ident.clone().map_or_else(
+                                || {
+                                    Member::Unnamed(Index {
+                                        index,
+                                        span: Span::call_site(),
+                                    })
+                                },
+                                Member::Named,
+                            );
+
+                            quote! { #(#field_cfg_attrs)* #member: #binding }
+                        },
+                    );
+
+                let fields = variant.fields.iter().zip(&bindings).map(
+                    |(field, binding)| DebugField {
+                        cfg_attrs: util::cfg_attrs(&field.attrs),
+                        name: field.ident.as_ref().map(ident_name),
+                        value: quote! { #binding },
+                    },
+                );
+                let body = expand_fields(
+                    &ident_name(variant_ident),
+                    &variant.fields,
+                    fields,
+                );
+
+                quote! {
+                    #(#variant_cfg_attrs)*
+                    Self::#variant_ident { #(#patterns),* } => { #body }
+                }
+            });
+
+            if data_enum.variants.is_empty() {
+                return quote! { match *self {} };
+            }
+
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+
+        Data::Union(_) => TokenStream2::new(),
+    }
+}
+
+struct DebugField {
+    cfg_attrs: Vec<Attribute>,
+    name: Option<LitStr>,
+    value: TokenStream2,
+}
+
+fn expand_fields<I>(
+    name: &LitStr,
+    fields: &Fields,
+    debug_fields: I,
+) -> TokenStream2
+where
+    I: Iterator<Item = DebugField>,
+{
+    let builder = match *fields {
+        Fields::Named(_) => quote! { f.debug_struct(#name) },
+        Fields::Unnamed(_) => quote! { f.debug_tuple(#name) },
+        Fields::Unit => {
+            drop(debug_fields);
+            return quote! { f.write_str(#name) };
+        }
+    };
+
+    let field_calls = debug_fields.map(|field| {
+        let DebugField {
+            cfg_attrs,
+            name: field_lit_str,
+            value,
+        } = field;
+        let field_name =
+            field_lit_str.as_ref().map(|lit_str| quote! { #lit_str, });
+
+        quote! {
+            #(#cfg_attrs)*
+            debug_builder.field(#field_name #value);
+        }
+    });
+
+    quote! {
+        let mut debug_builder = #builder;
+        #(#field_calls)*
+        debug_builder.finish()
+    }
+}
+
+fn ident_name(ident: &Ident) -> LitStr {
+    LitStr::new(&ident.unraw().to_string(), ident.span())
+}
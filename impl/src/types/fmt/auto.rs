@@ -0,0 +1,231 @@
+use std::mem;
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::{
+    Ident, LitStr, Token,
+    ext::IdentExt as _,
+    parse::{Parse, ParseStream},
+    token::Comma,
+};
+
+use super::{pad, template::Owner};
+
+mod kw {
+    syn::custom_keyword!(auto);
+}
+
+#[derive(Clone)]
+pub(crate) struct AutoInput {
+    keyword_span: Span,
+    case: Case,
+    prefix: Option<LitStr>,
+    suffix: Option<LitStr>,
+    message: Option<LitStr>,
+}
+
+impl Parse for AutoInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let keyword: kw::auto = input.parse()?;
+        let mut auto_input = Self {
+            keyword_span: keyword.span,
+            case: Case::Lower,
+            prefix: None,
+            suffix: None,
+            message: None,
+        };
+
+        let mut case_lit: Option<LitStr> = None;
+        while !input.is_empty() {
+            let _: Comma = input.parse()?;
+            if input.is_empty() {
+                break;
+            }
+
+            let key = input.call(Ident::parse_any)?;
+            let _: Token![=] = input.parse()?;
+            let value: LitStr = input.parse()?;
+
+            let slot = match key.to_string().as_str() {
+                "case" => &mut case_lit,
+                "prefix" => &mut auto_input.prefix,
+                "suffix" => &mut auto_input.suffix,
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "unknown `auto` key\nexpected `case`, `prefix` or `suffix`",
+                    ));
+                }
+            };
+
+            if slot.is_some() {
+                return Err(syn::Error::new(
+                    key.span(),
+                    format!("duplicate `{key}` key"),
+                ));
+            }
+
+            *slot = Some(value);
+        }
+
+        if let Some(lit_str) = case_lit {
+            auto_input.case = Case::from_lit_str(&lit_str)?;
+        }
+
+        Ok(auto_input)
+    }
+}
+
+impl AutoInput {
+    pub(crate) fn peek(input: ParseStream) -> bool {
+        input.peek(kw::auto)
+    }
+
+    pub(crate) fn resolve_message(&mut self, owner: &Owner) -> syn::Result<()> {
+        let Some(variant_ident) = owner.variant_ident() else {
+            return Err(syn::Error::new(
+                self.keyword_span,
+                "`auto` is only supported on enums and enum variants",
+            ));
+        };
+
+        let words = split_words(&variant_ident.unraw().to_string());
+        let mut message = self.case.join(&words);
+        drop(words);
+
+        if let Some(ref prefix) = self.prefix {
+            message.insert_str(0, &prefix.value());
+        }
+
+        if let Some(ref suffix) = self.suffix {
+            message.push_str(&suffix.value());
+        }
+
+        self.message = Some(LitStr::new(&message, self.keyword_span));
+        Ok(())
+    }
+
+    pub(crate) const fn message(&self) -> Option<&LitStr> {
+        self.message.as_ref()
+    }
+
+    pub(crate) fn expand(&self, pad: bool) -> TokenStream2 {
+        let Some(ref message) = self.message else {
+            return TokenStream2::new();
+        };
+
+        pad::write_literal(message, pad)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Case {
+    Lower,
+    Sentence,
+    Snake,
+    Kebab,
+    ScreamingSnake,
+}
+
+impl Case {
+    fn from_lit_str(lit_str: &LitStr) -> syn::Result<Self> {
+        match lit_str.value().as_str() {
+            "lower case" => Ok(Self::Lower),
+            "Sentence case" => Ok(Self::Sentence),
+            "snake_case" => Ok(Self::Snake),
+            "kebab-case" => Ok(Self::Kebab),
+            "SCREAMING_SNAKE_CASE" => Ok(Self::ScreamingSnake),
+            _ => Err(syn::Error::new(
+                lit_str.span(),
+                "unknown case style\nexpected `lower case`, `Sentence case`, `snake_case`, `kebab-case` or `SCREAMING_SNAKE_CASE`",
+            )),
+        }
+    }
+
+    fn join(self, words: &[String]) -> String {
+        let separator = match self {
+            Self::Lower | Self::Sentence => " ",
+            Self::Snake | Self::ScreamingSnake => "_",
+            Self::Kebab => "-",
+        };
+
+        let mut joined = words
+            .iter()
+            .map(|word| match self {
+                Self::ScreamingSnake => word.to_uppercase(),
+                Self::Lower | Self::Sentence | Self::Snake | Self::Kebab => {
+                    word.to_lowercase()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(separator);
+
+        if matches!(self, Self::Sentence) {
+            let mut chars = joined.chars();
+            if let Some(first) = chars.next() {
+                joined = first.to_uppercase().chain(chars).collect();
+            }
+        }
+
+        joined
+    }
+}
+
+/// Splits an identifier into words at underscores and case changes, keeping
+/// acronyms together (`HTTPRequestFailed` becomes `HTTP`, `Request` and
+/// `Failed`).
+fn split_words(ident: &str) -> Vec<String> {
+    let mut words = Vec::new();
+
+    for part in ident.split('_').filter(|part| !part.is_empty()) {
+        let mut word = String::new();
+        let mut chars = part.chars().peekable();
+        let mut prev: Option<char> = None;
+
+        while let Some(ch) = chars.next() {
+            let starts_word = prev.is_some_and(|prev_ch| {
+                ch.is_uppercase()
+                    && (!prev_ch.is_uppercase()
+                        || chars.peek().is_some_and(|next| next.is_lowercase()))
+            });
+
+            if starts_word && !word.is_empty() {
+                words.push(mem::take(&mut word));
+            }
+
+            word.push(ch);
+            prev = Some(ch);
+        }
+
+        if !word.is_empty() {
+            words.push(word);
+        }
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifiers_are_split_into_words() {
+        assert_eq!(split_words("NotFound"), ["Not", "Found"]);
+        assert_eq!(
+            split_words("HTTPRequestFailed"),
+            ["HTTP", "Request", "Failed"]
+        );
+        assert_eq!(split_words("Io2Error"), ["Io2", "Error"]);
+        assert_eq!(split_words("permission_denied"), ["permission", "denied"]);
+    }
+
+    #[test]
+    fn case_styles_join_words() {
+        let words = split_words("PermissionDenied");
+        assert_eq!(Case::Lower.join(&words), "permission denied");
+        assert_eq!(Case::Sentence.join(&words), "Permission denied");
+        assert_eq!(Case::Snake.join(&words), "permission_denied");
+        assert_eq!(Case::Kebab.join(&words), "permission-denied");
+        assert_eq!(Case::ScreamingSnake.join(&words), "PERMISSION_DENIED");
+    }
+}
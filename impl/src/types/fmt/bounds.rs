@@ -0,0 +1,55 @@
+#[cfg(test)]
+use std::fmt::{self, Debug, Formatter};
+
+use proc_macro2::{Ident, Span};
+use quote::{ToTokens as _, quote};
+use syn::{Type, WherePredicate};
+
+use super::super::util;
+
+pub(crate) struct FieldBound {
+    ty: Type,
+    trait_ident: Ident,
+}
+
+#[cfg(test)]
+impl Debug for FieldBound {
+    fn fmt(&self, _: &mut Formatter<'_>) -> fmt::Result {
+        Ok(())
+    }
+}
+
+impl FieldBound {
+    pub(crate) fn new(ty: &Type, format_trait: &str) -> Option<Self> {
+        let trait_name = match format_trait {
+            "" => "Display",
+            "?" | "x?" | "X?" => "Debug",
+            "x" => "LowerHex",
+            "X" => "UpperHex",
+            "o" => "Octal",
+            "b" => "Binary",
+            "e" => "LowerExp",
+            "E" => "UpperExp",
+            _ => return None,
+        };
+
+        Some(Self {
+            ty: ty.clone(),
+            trait_ident: Ident::new(trait_name, Span::call_site()),
+        })
+    }
+
+    pub(crate) fn mentions_any(&self, type_params: &[&Ident]) -> bool {
+        util::tokens_mention_any(self.ty.to_token_stream(), type_params)
+    }
+
+    pub(crate) fn to_predicate(&self) -> WherePredicate {
+        let Self {
+            ref ty,
+            ref trait_ident,
+        } = *self;
+
+        #[expect(clippy::unwrap_used, reason = "this `TokenStream` is valid")]
+        syn::parse2(quote! { #ty: ::core::fmt::#trait_ident }).unwrap()
+    }
+}
@@ -0,0 +1,156 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{ToTokens, quote};
+use syn::{
+    Attribute, Fields, Ident, Index, Member, Path, Type, Variant,
+    punctuated::Punctuated, spanned::Spanned as _, token::Comma,
+};
+
+use super::super::attr;
+
+pub(crate) enum EmptyType {
+    NoVariants,
+    Field(Member),
+    Variants(Vec<UninhabitedVariant>),
+}
+
+pub(crate) struct UninhabitedVariant {
+    cfg_attrs: Vec<Attribute>,
+    ident: Ident,
+    member: Member,
+}
+
+impl EmptyType {
+    pub(crate) fn from_fields(fields: &Fields) -> syn::Result<Option<Self>> {
+        Ok(uninhabited_member(fields)?.map(Self::Field))
+    }
+
+    pub(crate) fn from_variants(
+        variants: &Punctuated<Variant, Comma>,
+    ) -> syn::Result<Option<Self>> {
+        if variants.is_empty() {
+            return Ok(Some(Self::NoVariants));
+        }
+
+        let mut uninhabited_variants = Vec::new();
+        for variant in variants {
+            let Some(member) = uninhabited_member(&variant.fields)? else {
+                return Ok(None);
+            };
+
+            uninhabited_variants.push(UninhabitedVariant {
+                cfg_attrs: variant
+                    .attrs
+                    .iter()
+                    .filter(|variant_attr| variant_attr.path().is_ident("cfg"))
+                    .cloned()
+                    .collect(),
+                ident: variant.ident.clone(),
+                member,
+            });
+        }
+
+        Ok(Some(Self::Variants(uninhabited_variants)))
+    }
+}
+
+impl ToTokens for EmptyType {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        match *self {
+            Self::NoVariants => tokens.extend(quote! {
+                match *self {}
+            }),
+
+            Self::Field(ref member) => tokens.extend(quote! {
+                match self.#member {}
+            }),
+
+            Self::Variants(ref variants) => {
+                let arms = variants.iter().map(|variant| {
+                    let UninhabitedVariant {
+                        ref cfg_attrs,
+                        ref ident,
+                        ref member,
+                    } = *variant;
+
+                    quote! {
+                        #(#cfg_attrs)*
+                        Self::#ident { #member: ref uninhabited, .. } => match *uninhabited {}
+                    }
+                });
+
+                tokens.extend(quote! {
+                    match *self {
+                        #(#arms),*
+                    }
+                });
+            }
+        }
+    }
+}
+
+fn uninhabited_member(fields: &Fields) -> syn::Result<Option<Member>> {
+    for (index, field) in fields.iter().enumerate() {
+        if !is_uninhabited_type(&field.ty)
+            && !attr::is_marked_uninhabited(&field.attrs)?
+        {
+            continue;
+        }
+
+        let member = field.ident.clone().map_or_else(
+            || {
+                Member::Unnamed(Index {
+                    index: u32::try_from(index).unwrap_or(u32::MAX),
+                    span: field.ty.span(),
+                })
+            },
+            Member::Named,
+        );
+
+        return Ok(Some(member));
+    }
+
+    Ok(None)
+}
+
+/// Whether `ty` is known to be uninhabited from its tokens alone, which is the
+/// case for `!` and `Infallible`. Type aliases and imports cannot be resolved
+/// by the macro, so `Infallible` is only recognized as a bare name, assumed to
+/// be imported from `core::convert`, or as the full `core::convert` or
+/// `std::convert` path. Other uninhabited types need `#[error(uninhabited)]`.
+fn is_uninhabited_type(ty: &Type) -> bool {
+    match *ty {
+        Type::Never(_) => true,
+        Type::Group(ref group) => is_uninhabited_type(&group.elem),
+        Type::Paren(ref paren) => is_uninhabited_type(&paren.elem),
+        Type::Path(ref type_path) => {
+            type_path.qself.is_none() && is_infallible_path(&type_path.path)
+        }
+        _ => false,
+    }
+}
+
+fn is_infallible_path(path: &Path) -> bool {
+    if path
+        .segments
+        .iter()
+        .any(|segment| !segment.arguments.is_empty())
+    {
+        return false;
+    }
+
+    let names: Vec<String> = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+
+    match *names.as_slice() {
+        [ref name] => path.leading_colon.is_none() && name == "Infallible",
+        [ref krate, ref module, ref name] => {
+            (krate == "core" || krate == "std")
+                && module == "convert"
+                && name == "Infallible"
+        }
+        _ => false,
+    }
+}
@@ -2,18 +2,36 @@
 use std::fmt::{self, Debug, Formatter};
 
 use proc_macro2::{Span, TokenStream as TokenStream2};
-use quote::{ToTokens, quote};
-use regex::Regex;
+use quote::{quote, quote_spanned};
 use syn::{
-    Expr, Ident, LitInt, LitStr,
+    Attribute, Fields, Ident, Index, LitStr, Member, Meta,
     parse::{Parse, ParseStream},
-    punctuated::Punctuated,
+    spanned::Spanned as _,
     token::Comma,
 };
 
-pub(crate) struct StructFormatInput {
-    lit_str: LitStr,
-    args: Punctuated<Expr, Comma>,
+use super::{
+    auto::AutoInput,
+    bounds::FieldBound,
+    pad,
+    template::{self, FieldAccess, FormatArgs, Owner, Template},
+    wrap::Wrapper,
+};
+
+mod kw {
+    syn::custom_keyword!(doc);
+    syn::custom_keyword!(transparent);
+}
+
+/// Whether `attr` is `#[display(doc)]`, which takes the template from the doc
+/// comments instead.
+pub(crate) fn is_doc_display_attr(attr: &Attribute) -> bool {
+    matches!(attr.meta, Meta::List(_)) && attr.parse_args::<kw::doc>().is_ok()
+}
+
+pub(crate) enum StructFormatInput {
+    Template(Template),
+    Transparent(TransparentInput),
 }
 
 #[cfg(test)]
@@ -25,74 +43,89 @@ impl Debug for StructFormatInput {
 
 impl Parse for StructFormatInput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let input_lit_str: LitStr = input.parse()?;
-        if !input.is_empty() {
+        if input.peek(kw::transparent) {
+            return Ok(Self::Transparent(input.parse()?));
+        }
+
+        if AutoInput::peek(input) {
             return Err(syn::Error::new(
                 input.span(),
-                "unexpected token after string literal",
+                "`auto` is only supported on enums and enum variants",
             ));
         }
 
-        #[expect(
-            clippy::unwrap_used,
-            reason = "this pattern is valid and the regex is under the size limit"
-        )]
-        let regex = Regex::new(r"\{(\w+)(?::.+?)?\}").unwrap();
-        let mut fmt_string = input_lit_str.value();
-        let mut args = Punctuated::new();
-
-        let lit_str_span = input_lit_str.span();
-        drop(input_lit_str);
-
-        while let Some(captures) = regex.captures(&fmt_string) {
-            #[expect(
-                clippy::unwrap_used,
-                reason = "the first capture group is guaranteed to appear"
-            )]
-            let group = captures.get(1).unwrap();
-            drop(captures);
-
-            let inline_arg_str = group.as_str();
-
-            let arg_tokens = if inline_arg_str.parse::<usize>().is_ok() {
-                let lit_int = LitInt::new(inline_arg_str, Span::call_site());
-                quote! { &self.#lit_int }
-            } else {
-                let ident = Ident::new(inline_arg_str, Span::call_site());
-                quote! { &self.#ident }
-            };
+        Ok(Self::Template(parse_template(input)?))
+    }
+}
 
-            let arg_expr = syn::parse2(arg_tokens)?;
-            args.push(arg_expr);
+impl StructFormatInput {
+    pub(crate) fn from_doc(lit_str: LitStr) -> syn::Result<Self> {
+        Template::from_doc(lit_str).map(Self::Template)
+    }
 
-            fmt_string.replace_range(group.range(), "");
+    pub(crate) fn resolve_fields(
+        &mut self,
+        fields: &Fields,
+        type_ident: &Ident,
+    ) -> syn::Result<Vec<FieldBound>> {
+        let owner = Owner::for_struct(type_ident);
+        match *self {
+            Self::Template(ref mut template) => template.resolve(fields, owner),
+            Self::Transparent(ref mut transparent) => {
+                transparent.resolve_field(fields, owner.description())
+            }
         }
+    }
 
-        drop(regex);
-
-        let lit_str = LitStr::new(&fmt_string, lit_str_span);
-        drop(fmt_string);
+    pub(crate) fn templates(&self) -> Vec<&Template> {
+        match *self {
+            Self::Template(ref template) => vec![template],
+            Self::Transparent(_) => Vec::new(),
+        }
+    }
 
-        Ok(Self { lit_str, args })
+    /// Whether the message is known at compile time.
+    pub(crate) fn is_static(&self) -> bool {
+        match *self {
+            Self::Template(ref template) => {
+                template.literal_message().is_some()
+            }
+            Self::Transparent(_) => false,
+        }
     }
-}
 
-impl ToTokens for StructFormatInput {
-    fn to_tokens(&self, tokens: &mut TokenStream2) {
-        let Self {
-            ref lit_str,
-            ref args,
-        } = *self;
+    /// Expands to the body of the `message` accessor, which returns an
+    /// `Option` if `optional` is set.
+    pub(crate) fn expand_message(&self, optional: bool) -> TokenStream2 {
+        match *self {
+            Self::Template(ref template) => {
+                expand_static_message(template.literal_message(), optional)
+            }
+            Self::Transparent(_) => expand_static_message(None, optional),
+        }
+    }
 
-        tokens.extend(quote! {
-            #lit_str, #args
-        });
+    /// Expands to the body of `fmt`, where `pad` writes literal messages with
+    /// `f.pad`.
+    pub(crate) fn expand(&self, pad: bool) -> TokenStream2 {
+        match *self {
+            Self::Template(ref template) => template
+                .expand_with(&FieldAccess::SelfRef, |lit_str, args| {
+                    pad::write_message(&lit_str, &args, pad)
+                }),
+
+            Self::Transparent(ref transparent) => {
+                transparent.expand(&FieldAccess::SelfRef)
+            }
+        }
     }
 }
 
-pub(crate) struct VariantFormatInput {
-    lit_str: LitStr,
-    args: Punctuated<Ident, Comma>,
+#[derive(Clone)]
+pub(crate) enum VariantFormatInput {
+    Template(Template),
+    Transparent(TransparentInput),
+    Auto(AutoInput),
 }
 
 #[cfg(test)]
@@ -104,67 +137,250 @@ impl Debug for VariantFormatInput {
 
 impl Parse for VariantFormatInput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let input_lit_str: LitStr = input.parse()?;
-        if !input.is_empty() {
-            return Err(syn::Error::new(
-                input.span(),
-                "unexpected token after string literal",
-            ));
+        if input.peek(kw::transparent) {
+            return Ok(Self::Transparent(input.parse()?));
         }
 
-        #[expect(
-            clippy::unwrap_used,
-            reason = "this pattern is valid and the regex is under the size limit"
-        )]
-        let regex = Regex::new(r"\{(\w+)(?::.+?)?\}").unwrap();
-        let mut fmt_string = input_lit_str.value();
-        let mut args = Punctuated::new();
+        if AutoInput::peek(input) {
+            return Ok(Self::Auto(input.parse()?));
+        }
 
-        let lit_str_span = input_lit_str.span();
-        drop(input_lit_str);
+        Ok(Self::Template(parse_template(input)?))
+    }
+}
 
-        while let Some(captures) = regex.captures(&fmt_string) {
-            #[expect(
-                clippy::unwrap_used,
-                reason = "the first capture group is guaranteed to appear"
-            )]
-            let group = captures.get(1).unwrap();
-            drop(captures);
+impl VariantFormatInput {
+    pub(crate) fn from_doc(lit_str: LitStr) -> syn::Result<Self> {
+        Template::from_doc(lit_str).map(Self::Template)
+    }
 
-            let inline_arg_str = group.as_str();
+    pub(crate) fn resolve_fields(
+        &mut self,
+        fields: &Fields,
+        owner: Owner,
+    ) -> syn::Result<Vec<FieldBound>> {
+        match *self {
+            Self::Template(ref mut template) => template.resolve(fields, owner),
+            Self::Transparent(ref mut transparent) => {
+                transparent.resolve_field(fields, owner.description())
+            }
+            Self::Auto(ref mut auto) => {
+                auto.resolve_message(&owner)?;
+                Ok(Vec::new())
+            }
+        }
+    }
 
-            let ident_str = if inline_arg_str.parse::<usize>().is_ok() {
-                &format!("_field{inline_arg_str}")
-            } else {
-                inline_arg_str
-            };
+    pub(crate) fn depends_on_variant(&self) -> bool {
+        match *self {
+            Self::Template(ref template) => template.depends_on_variant(),
+            Self::Transparent(_) | Self::Auto(_) => true,
+        }
+    }
 
-            let ident = Ident::new(ident_str, Span::call_site());
-            args.push(ident);
+    pub(crate) fn templates(&self) -> Vec<&Template> {
+        match *self {
+            Self::Template(ref template) => vec![template],
+            Self::Transparent(_) | Self::Auto(_) => Vec::new(),
+        }
+    }
 
-            fmt_string.replace_range(group.range(), "");
+    /// Returns the message if it is known at compile time, written into
+    /// `wrapper` if there is one.
+    pub(crate) fn static_message(
+        &self,
+        wrapper: Option<&Wrapper>,
+    ) -> Option<LitStr> {
+        let message = match *self {
+            Self::Template(ref template) => template.literal_message(),
+            Self::Transparent(_) => None,
+            Self::Auto(ref auto) => auto.message().cloned(),
+        }?;
+
+        match wrapper {
+            Some(enum_wrapper) => enum_wrapper.apply(&message),
+            None => Some(message),
+        }
+    }
+
+    /// Expands to the body of a match arm of the `message` accessor, which
+    /// returns an `Option` if `optional` is set.
+    pub(crate) fn expand_message(
+        &self,
+        optional: bool,
+        wrapper: Option<&Wrapper>,
+    ) -> TokenStream2 {
+        expand_static_message(self.static_message(wrapper), optional)
+    }
+
+    /// Expands to the body of a match arm, where `pad` writes literal
+    /// messages with `f.pad`.
+    pub(crate) fn expand(&self, pad: bool) -> TokenStream2 {
+        match *self {
+            Self::Template(ref template) => template
+                .expand_with(&FieldAccess::Bindings, |lit_str, args| {
+                    pad::write_message(&lit_str, &args, pad)
+                }),
+
+            Self::Transparent(ref transparent) => {
+                transparent.expand(&FieldAccess::Bindings)
+            }
+
+            Self::Auto(ref auto) => auto.expand(pad),
+        }
+    }
+
+    /// Expands to a single `write!` of the message into `wrapper`. Messages
+    /// without placeholders are passed as strings, as those of `auto` are.
+    pub(crate) fn expand_wrapped(&self, wrapper: &Wrapper) -> TokenStream2 {
+        match *self {
+            Self::Template(ref template) => {
+                template.expand_with(&FieldAccess::Bindings, |lit_str, args| {
+                    if args.is_empty() {
+                        let message = LitStr::new(
+                            &template::unescape_braces(&lit_str.value()),
+                            lit_str.span(),
+                        );
+                        return wrapper.write(quote! { #message });
+                    }
+
+                    wrapper.write(quote! {
+                        ::core::format_args!(#lit_str, #(#args),*)
+                    })
+                })
+            }
+
+            Self::Transparent(ref transparent) => transparent
+                .display_arg(&FieldAccess::Bindings)
+                .map(|arg| wrapper.write(arg))
+                .unwrap_or_default(),
+
+            Self::Auto(ref auto) => auto
+                .message()
+                .map(|message| wrapper.write(quote! { #message }))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct TransparentInput {
+    keyword_span: Span,
+    field: Option<TransparentField>,
+}
+
+#[derive(Clone)]
+struct TransparentField {
+    member: Member,
+    ty_span: Span,
+}
+
+impl Parse for TransparentInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let keyword: kw::transparent = input.parse()?;
+        if !input.is_empty() {
+            return Err(syn::Error::new(
+                input.span(),
+                "unexpected token after `transparent`",
+            ));
         }
 
-        drop(regex);
+        Ok(Self {
+            keyword_span: keyword.span,
+            field: None,
+        })
+    }
+}
 
-        let lit_str = LitStr::new(&fmt_string, lit_str_span);
-        drop(fmt_string);
+impl TransparentInput {
+    fn resolve_field(
+        &mut self,
+        fields: &Fields,
+        owner: &str,
+    ) -> syn::Result<Vec<FieldBound>> {
+        let mut fields_iter = fields.iter();
+        let (Some(field), None) = (fields_iter.next(), fields_iter.next())
+        else {
+            return Err(syn::Error::new(
+                self.keyword_span,
+                format!(
+                    "`transparent` requires exactly one field, but {owner} has {}",
+                    match fields.len() {
+                        0 => String::from("none"),
+                        len => len.to_string(),
+                    }
+                ),
+            ));
+        };
+
+        let ty_span = field.ty.span();
+        let member = field.ident.clone().map_or_else(
+            || {
+                Member::Unnamed(Index {
+                    index: 0,
+                    span: ty_span,
+                })
+            },
+            Member::Named,
+        );
 
-        Ok(Self { lit_str, args })
+        self.field = Some(TransparentField { member, ty_span });
+        Ok(FieldBound::new(&field.ty, "").into_iter().collect())
+    }
+
+    /// Returns the reference to the field that the message is forwarded to.
+    fn display_arg(&self, access: &FieldAccess) -> Option<TokenStream2> {
+        let field = self.field.as_ref()?;
+        let member = &field.member;
+        let span = Span::call_site().located_at(field.ty_span);
+
+        Some(match *access {
+            FieldAccess::SelfRef => quote_spanned! {span=> &self.#member },
+            FieldAccess::Bindings => {
+                let binding = template::binding_ident(member);
+                quote_spanned! {span=> #binding }
+            }
+        })
+    }
+
+    fn expand(&self, access: &FieldAccess) -> TokenStream2 {
+        let (Some(arg), Some(field)) =
+            (self.display_arg(access), self.field.as_ref())
+        else {
+            return TokenStream2::new();
+        };
+
+        let span = Span::call_site().located_at(field.ty_span);
+        quote_spanned! {span=>
+            ::core::fmt::Display::fmt(#arg, f)
+        }
     }
 }
 
-impl ToTokens for VariantFormatInput {
-    fn to_tokens(&self, tokens: &mut TokenStream2) {
-        let Self {
-            ref lit_str,
-            ref args,
-        } = *self;
+fn expand_static_message(
+    message: Option<LitStr>,
+    optional: bool,
+) -> TokenStream2 {
+    match (message, optional) {
+        (Some(lit_str), false) => quote! { #lit_str },
+        (Some(lit_str), true) => {
+            quote! { ::core::option::Option::Some(#lit_str) }
+        }
+        (None, _) => quote! { ::core::option::Option::None },
+    }
+}
 
-        tokens.extend(quote! {
-            #lit_str, #args
-        });
+fn parse_template(input: ParseStream) -> syn::Result<Template> {
+    let input_lit_str: LitStr = input.parse()?;
+    if !input.is_empty() && !input.peek(Comma) {
+        return Err(syn::Error::new(
+            input.span(),
+            "unexpected token after string literal",
+        ));
     }
+
+    let args: FormatArgs = input.parse()?;
+    Template::new(input_lit_str, args)
 }
 
 #[cfg(test)]
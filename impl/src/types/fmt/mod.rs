@@ -1,91 +1,128 @@
-use std::convert::Infallible;
 #[cfg(test)]
 use std::fmt::{self, Debug, Formatter};
+use std::{convert::Infallible, mem};
 
 use proc_macro2::{Span, TokenStream as TokenStream2};
-use quote::{ToTokens, quote};
-use syn::{
-    Attribute, Data, Fields, Ident, LitStr, Type, spanned::Spanned as _,
-};
+use quote::quote;
+use syn::{Attribute, Data, Fields, Ident, Visibility, spanned::Spanned as _};
+
+use super::attr::ForwardAttrs;
+
+mod auto;
+
+mod bounds;
+pub(crate) use bounds::FieldBound;
+
+mod empty;
+use empty::EmptyType;
 
 mod input;
-use input::{StructFormatInput, VariantFormatInput};
+use input::{StructFormatInput, VariantFormatInput, is_doc_display_attr};
+
+mod pad;
+
+mod template;
+use template::Owner;
+pub(crate) use template::Template;
 
 mod util;
 
+mod wrap;
+pub(crate) use wrap::Wrapper;
+
 pub(crate) enum TypeData {
     Struct {
         display_input: StructFormatInput,
+        field_bounds: Vec<FieldBound>,
     },
 
     Enum {
-        default_display_input: Option<LitStr>,
+        default_display_input: Option<VariantFormatInput>,
         variant_display_inputs: Vec<VariantData>,
+        fallback_variants: Vec<FallbackVariant>,
+        wrapper: Option<Wrapper>,
     },
 
-    EmptyType,
+    /// An uninhabited type, along with the span of its `display` attribute,
+    /// which is never used.
+    EmptyType(EmptyType, Option<Span>),
 }
 
 impl TypeData {
     pub(crate) fn new(
         input_data: Data,
         attrs: &mut Vec<Attribute>,
-        ident_span: Span,
+        ident: &Ident,
     ) -> syn::Result<Self> {
-        let default_display_attr = super::util::take_display_attr(attrs);
+        let ident_span = ident.span();
+        let default_display_attr = super::util::take_display_attr(attrs)?;
+        let uses_docs = default_display_attr
+            .as_ref()
+            .is_some_and(is_doc_display_attr);
 
         match input_data {
             Data::Struct(data) => {
-                let has_never_type = data
-                    .fields
-                    .iter()
-                    .any(|field| matches!(field.ty, Type::Never(_)));
-
-                drop(data);
+                if let Some(empty_type) = EmptyType::from_fields(&data.fields)?
+                {
+                    drop(data);
+                    let display_span =
+                        default_display_attr.map(|attr| attr.span());
+                    return Ok(Self::EmptyType(empty_type, display_span));
+                }
 
-                if has_never_type {
+                let mut display_input = if uses_docs {
                     drop(default_display_attr);
-                    return Ok(Self::EmptyType);
-                }
 
-                let display_attr = default_display_attr
-                    .ok_or_else(|| syn::Error::new(ident_span, "missing `display` attribute for struct with `#[derive(Error)]`"))?;
-                let display_input = util::get_format_input(display_attr)?;
+                    let doc = super::util::doc_paragraph(attrs)
+                        .ok_or_else(|| syn::Error::new(ident_span, "missing doc comment for struct with `#[display(doc)]`"))?;
+                    StructFormatInput::from_doc(doc)?
+                } else {
+                    let display_attr = default_display_attr
+                        .ok_or_else(|| syn::Error::new(ident_span, "missing `display` attribute for struct with `#[derive(Error)]`"))?;
+                    util::get_format_input(display_attr)?
+                };
+                let field_bounds =
+                    display_input.resolve_fields(&data.fields, ident)?;
+                drop(data);
 
-                Ok(Self::Struct { display_input })
+                Ok(Self::Struct {
+                    display_input,
+                    field_bounds,
+                })
             }
 
             Data::Enum(data) => {
                 let variants = data.variants;
-                if variants.is_empty() {
+                if let Some(empty_type) = EmptyType::from_variants(&variants)? {
                     drop(variants);
-                    return Ok(Self::EmptyType);
+                    let display_span =
+                        default_display_attr.map(|attr| attr.span());
+                    return Ok(Self::EmptyType(empty_type, display_span));
                 }
 
                 let variant_display_inputs =
-                    util::collect_valid_variant_states(variants)?;
+                    util::collect_valid_variant_states(
+                        variants, ident, uses_docs,
+                    )?;
 
-                if let Some(attr) = default_display_attr {
-                    let default_display_input =
-                        Some(util::get_format_input(attr)?);
+                if let Some(attr) = default_display_attr.filter(|_| !uses_docs)
+                {
+                    let default_display_input: VariantFormatInput =
+                        util::get_format_input(attr)?;
 
-                    return Ok(Self::Enum {
+                    return util::apply_default_display_input(
                         default_display_input,
-                        variant_display_inputs: variant_display_inputs
-                            .into_iter()
-                            .filter_map(VariantState::data)
-                            .collect(),
-                    });
+                        variant_display_inputs,
+                        ident,
+                    );
                 };
 
-                drop(default_display_attr);
-
                 let (valid_variants, none_spans) =
                     util::separate_existing_variant_states(
                         variant_display_inputs,
                     );
 
-                if valid_variants.is_empty() {
+                if valid_variants.is_empty() && !uses_docs {
                     drop(valid_variants);
                     drop(none_spans);
                     return Err(syn::Error::new(
@@ -103,7 +140,11 @@ impl TypeData {
                         .map(|span| {
                             syn::Error::new(
                                 span,
-                                "missing `display` attribute for variant in enum with `#[derive(Error)]`\nadd a `display` attribute either to the whole enum (as a default) or to the remaining variants"
+                                if uses_docs {
+                                    "missing doc comment for variant in enum with `#[display(doc)]`\nadd a doc comment or a `display` attribute to the remaining variants"
+                                } else {
+                                    "missing `display` attribute for variant in enum with `#[derive(Error)]`\nadd a `display` attribute either to the whole enum (as a default) or to the remaining variants"
+                                }
                             )
                         }).reduce(|mut err, err2| {
                             err.combine(err2);
@@ -116,6 +157,8 @@ impl TypeData {
                 Ok(Self::Enum {
                     default_display_input: None,
                     variant_display_inputs: valid_variants,
+                    fallback_variants: Vec::new(),
+                    wrapper: None,
                 })
             }
 
@@ -130,88 +173,364 @@ impl TypeData {
             }
         }
     }
-}
 
-#[cfg(test)]
-impl Debug for TypeData {
-    fn fmt(&self, _: &mut Formatter<'_>) -> fmt::Result {
+    pub(crate) fn filter_forwarded_attrs(
+        &mut self,
+        forward_attrs: &ForwardAttrs,
+    ) {
+        if let Self::Enum {
+            ref mut variant_display_inputs,
+            ..
+        } = *self
+        {
+            for variant in variant_display_inputs {
+                variant.other_attrs =
+                    forward_attrs.filter(mem::take(&mut variant.other_attrs));
+            }
+        }
+    }
+
+    /// Writes every variant message into `wrapper`, which is only supported
+    /// on enums. If the wrapper uses `{variant}`, the variants that fall back
+    /// to the enum-level message get a match arm each.
+    pub(crate) fn set_wrapper(
+        &mut self,
+        mut wrapper: Wrapper,
+        type_ident: &Ident,
+    ) -> syn::Result<()> {
+        let Self::Enum {
+            ref mut default_display_input,
+            ref mut variant_display_inputs,
+            ref mut fallback_variants,
+            wrapper: ref mut enum_wrapper,
+        } = *self
+        else {
+            return Err(syn::Error::new(
+                wrapper.span(),
+                "`wrap` and `prefix` are only supported on enums",
+            ));
+        };
+
+        wrapper.set_type(type_ident);
+        if wrapper.depends_on_variant()
+            && let Some(display_input) = default_display_input.take()
+        {
+            for fallback in mem::take(fallback_variants) {
+                variant_display_inputs.push(
+                    fallback.with_display_input(
+                        display_input.clone(),
+                        type_ident,
+                    )?,
+                );
+            }
+        }
+
+        *enum_wrapper = Some(wrapper);
         Ok(())
     }
-}
 
-impl ToTokens for TypeData {
-    fn to_tokens(&self, tokens: &mut TokenStream2) {
+    /// Returns the span of the `display` attribute of an uninhabited type,
+    /// which has no values to format.
+    pub(crate) const fn unused_display_span(&self) -> Option<Span> {
         match *self {
-            Self::Struct { ref display_input } => {
-                tokens.extend(quote! {
-                    ::core::write!(f, #display_input)
-                });
-            }
+            Self::EmptyType(_, display_span) => display_span,
+            Self::Struct { .. } | Self::Enum { .. } => None,
+        }
+    }
+
+    pub(crate) fn field_bounds(&self) -> Vec<&FieldBound> {
+        match *self {
+            Self::Struct {
+                ref field_bounds, ..
+            } => field_bounds.iter().collect(),
+
+            Self::Enum {
+                ref variant_display_inputs,
+                ..
+            } => variant_display_inputs
+                .iter()
+                .flat_map(|variant| &variant.field_bounds)
+                .collect(),
+
+            Self::EmptyType(..) => Vec::new(),
+        }
+    }
+
+    /// Expands to the body of `fmt`. With `pad`, the width, fill and
+    /// precision of the caller are applied to the message.
+    pub(crate) fn expand(&self, pad: bool) -> TokenStream2 {
+        let fallback =
+            (pad && self.needs_padding_fallback()).then(pad::expand_fallback);
+
+        let body = match *self {
+            Self::Struct {
+                ref display_input, ..
+            } => display_input.expand(pad),
 
             Self::Enum {
                 ref default_display_input,
                 ref variant_display_inputs,
+                ref wrapper,
+                ..
             } => {
+                let expand_input =
+                    |input: &VariantFormatInput,
+                     input_wrapper: Option<&Wrapper>| {
+                        input_wrapper.map_or_else(
+                            || input.expand(pad),
+                            |enum_wrapper| input.expand_wrapped(enum_wrapper),
+                        )
+                    };
+
                 let branches = variant_display_inputs
                     .iter()
                     .map(|variant| {
-                        quote! { #variant }
+                        let pattern = variant.pattern();
+                        let variant_wrapper = variant.wrapper(wrapper.as_ref());
+                        let body = expand_input(
+                            &variant.display_input,
+                            variant_wrapper.as_ref(),
+                        );
+                        quote! { #pattern => #body }
                     })
-                    .chain(default_display_input.as_ref().map(|lit_str| {
+                    .chain(default_display_input.as_ref().map(|input| {
+                        let body = expand_input(input, wrapper.as_ref());
                         quote! {
-                           _ => ::core::write!(f, #lit_str)
+                           _ => #body
                         }
                     }));
 
-                tokens.extend(quote! {
+                quote! {
                    match &self {
                        #(#branches),*
                    }
-                });
+                }
+            }
+
+            Self::EmptyType(ref empty_type, _) => quote! { #empty_type },
+        };
+
+        quote! {
+            #fallback
+            #body
+        }
+    }
+
+    /// Expands to the `message` accessor, which returns the message when it
+    /// is known at compile time. It returns a plain `&'static str` if that is
+    /// the case for every variant. Uninhabited types have no messages, so
+    /// they get no accessor.
+    pub(crate) fn expand_message_fn(
+        &self,
+        vis: &Visibility,
+    ) -> Option<TokenStream2> {
+        let optional = !self.has_static_messages();
+
+        let body = match *self {
+            Self::Struct {
+                ref display_input, ..
+            } => display_input.expand_message(optional),
+
+            Self::Enum {
+                ref default_display_input,
+                ref variant_display_inputs,
+                ref wrapper,
+                ..
+            } => {
+                let branches = variant_display_inputs
+                    .iter()
+                    .map(|variant| {
+                        variant.expand_message_arm(optional, wrapper.as_ref())
+                    })
+                    .chain(default_display_input.as_ref().map(|input| {
+                        let body =
+                            input.expand_message(optional, wrapper.as_ref());
+                        quote! {
+                           _ => #body
+                        }
+                    }));
+
+                quote! {
+                   match self {
+                       #(#branches),*
+                   }
+                }
             }
 
-            Self::EmptyType => {
-                tokens.extend(quote! {
-                    ::core::unreachable!("attempted to format an empty type")
-                });
+            Self::EmptyType(..) => return None,
+        };
+
+        Some(if optional {
+            quote! {
+                /// Returns the message of this error, or `None` if it is
+                /// formatted at runtime.
+                #[must_use]
+                #vis const fn message(&self) -> ::core::option::Option<&'static str> {
+                    #body
+                }
+            }
+        } else {
+            quote! {
+                /// Returns the message of this error.
+                #[must_use]
+                #vis const fn message(&self) -> &'static str {
+                    #body
+                }
+            }
+        })
+    }
+
+    fn has_static_messages(&self) -> bool {
+        match *self {
+            Self::Struct {
+                ref display_input, ..
+            } => display_input.is_static(),
+
+            Self::Enum {
+                ref default_display_input,
+                ref variant_display_inputs,
+                ref wrapper,
+                ..
+            } => {
+                variant_display_inputs.iter().all(|variant| {
+                    let variant_wrapper = variant.wrapper(wrapper.as_ref());
+                    variant
+                        .display_input
+                        .static_message(variant_wrapper.as_ref())
+                        .is_some()
+                }) && default_display_input.as_ref().is_none_or(|input| {
+                    input.static_message(wrapper.as_ref()).is_some()
+                })
             }
+
+            Self::EmptyType(..) => true,
         }
     }
+
+    /// Whether some message is not written with `f.pad`, so padding has to be
+    /// applied by the generated code. Transparent messages forward the
+    /// formatter and are padded by the field itself.
+    fn needs_padding_fallback(&self) -> bool {
+        matches!(
+            *self,
+            Self::Enum {
+                wrapper: Some(_),
+                ..
+            }
+        ) || self
+            .templates()
+            .into_iter()
+            .any(|template| !template.is_literal())
+    }
+
+    /// Returns the templates of all messages, which the lints check.
+    pub(crate) fn templates(&self) -> Vec<&Template> {
+        match *self {
+            Self::Struct {
+                ref display_input, ..
+            } => display_input.templates(),
+
+            Self::Enum {
+                ref default_display_input,
+                ref variant_display_inputs,
+                ..
+            } => variant_display_inputs
+                .iter()
+                .map(|variant| &variant.display_input)
+                .chain(default_display_input)
+                .flat_map(VariantFormatInput::templates)
+                .collect(),
+
+            Self::EmptyType(..) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl Debug for TypeData {
+    fn fmt(&self, _: &mut Formatter<'_>) -> fmt::Result {
+        Ok(())
+    }
 }
 
 enum VariantState<E> {
-    Valid(VariantData),
+    Valid(Box<VariantData>),
     Invalid(E),
-    None(Span),
+    None(Box<FallbackVariant>),
 }
 
-impl<E> VariantState<E> {
-    fn data(self) -> Option<VariantData> {
-        if let Self::Valid(data) = self {
-            Some(data)
-        } else {
-            drop(self);
-            None
-        }
-    }
+type ValidVariantState = VariantState<Infallible>;
+
+pub(crate) struct FallbackVariant {
+    span: Span,
+    cfg_attrs: Vec<Attribute>,
+    ident: Ident,
+    fields: Fields,
 }
 
-type ValidVariantState = VariantState<Infallible>;
+impl FallbackVariant {
+    fn with_display_input(
+        self,
+        mut display_input: VariantFormatInput,
+        type_ident: &Ident,
+    ) -> syn::Result<VariantData> {
+        let field_bounds = display_input.resolve_fields(
+            &self.fields,
+            Owner::for_fallback_variant(type_ident, &self.ident),
+        )?;
+
+        Ok(VariantData {
+            other_attrs: self.cfg_attrs,
+            ident: self.ident,
+            fields: self.fields,
+            display_input,
+            field_bounds,
+        })
+    }
+}
 
 pub(crate) struct VariantData {
     other_attrs: Vec<Attribute>,
     ident: Ident,
     fields: Fields,
     display_input: VariantFormatInput,
+    field_bounds: Vec<FieldBound>,
 }
 
-impl ToTokens for VariantData {
-    fn to_tokens(&self, tokens: &mut TokenStream2) {
+impl VariantData {
+    fn expand_message_arm(
+        &self,
+        optional: bool,
+        wrapper: Option<&Wrapper>,
+    ) -> TokenStream2 {
         let Self {
             ref other_attrs,
             ref ident,
-            ref fields,
             ref display_input,
+            ..
+        } = *self;
+        let body = display_input
+            .expand_message(optional, self.wrapper(wrapper).as_ref());
+
+        quote! {
+            #(#other_attrs)*
+            Self::#ident { .. } => #body
+        }
+    }
+
+    /// Returns the enum-level `wrapper` with `{variant}` referring to this
+    /// variant.
+    fn wrapper(&self, wrapper: Option<&Wrapper>) -> Option<Wrapper> {
+        wrapper.map(|enum_wrapper| enum_wrapper.for_variant(&self.ident))
+    }
+
+    /// Expands to the match arm pattern that binds the fields of the variant.
+    fn pattern(&self) -> TokenStream2 {
+        let Self {
+            ref other_attrs,
+            ref ident,
+            ref fields,
+            ..
         } = *self;
 
         let field_idents = fields.iter().enumerate().map(|(i, field)| {
@@ -220,19 +539,27 @@ impl ToTokens for VariantData {
             })
         });
 
-        let field_tokens = match *fields {
-            Fields::Named(_) => quote! { { #(#field_idents),* } },
-            Fields::Unnamed(_) => quote! { ( #(#field_idents),* ) },
+        // named fields keep their own names so that the template can refer
+        // to them, which makes the unused ones trip `unused_variables`
+        let (lint_attr, field_tokens) = match *fields {
+            Fields::Named(_) => (
+                quote! { #[allow(unused_variables)] },
+                quote! { { #(#field_idents),* } },
+            ),
+            Fields::Unnamed(_) => {
+                (TokenStream2::new(), quote! { ( #(#field_idents),* ) })
+            }
             Fields::Unit => {
                 drop(field_idents);
-                TokenStream2::new()
+                (TokenStream2::new(), TokenStream2::new())
             }
         };
 
-        tokens.extend(quote! {
+        quote! {
             #(#other_attrs)*
-            Self::#ident #field_tokens => ::core::write!(f, #display_input)
-        })
+            #lint_attr
+            Self::#ident #field_tokens
+        }
     }
 }
 
@@ -242,7 +569,10 @@ impl ToTokens for VariantData {
     reason = "this is a test module with calls to `.expect()`"
 )]
 mod tests {
-    use crate::ErrorStackDeriveInput;
+    use crate::{
+        ErrorStackDeriveInput,
+        types::tests::{expanded_impl, expanded_warnings},
+    };
 
     use super::*;
 
@@ -255,10 +585,137 @@ mod tests {
             syn::parse2(quote! { struct EmptyStructType(!); })
                 .expect("malformed test stream");
 
+        assert_eq!(
+            expanded_impl(&derive_input, Some("Display")),
+            "# [allow (single_use_lifetimes)] impl :: core :: fmt :: Display for EmptyStructType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { match self . 0 { } } }"
+        );
+    }
+
+    #[test]
+    fn infallible_struct_works_without_display_attr() {
+        let derive_input: ErrorStackDeriveInput = syn::parse2(quote! {
+            struct EmptyStructType {
+                code: u8,
+                never: core::convert::Infallible,
+            }
+        })
+        .expect("malformed test stream");
+
+        let output = quote! { #derive_input };
+        assert!(
+            output.to_string().contains(
+                ":: core :: fmt :: Result { match self . never { } } }"
+            )
+        );
+    }
+
+    #[test]
+    fn uninhabited_enum_works_without_display_attr() {
+        let derive_input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[display("unused default")]
+            enum EmptyEnumType {
+                #[cfg(true)]
+                One(Infallible),
+                Two {
+                    code: u8,
+                    #[error(uninhabited)]
+                    void: Void,
+                },
+            }
+        })
+        .expect("malformed test stream");
+
         let output = quote! { #derive_input };
+        assert!(output.to_string().contains(
+            ":: core :: fmt :: Result { match * self { # [cfg (true)] Self :: One { 0 : ref uninhabited , .. } => match * uninhabited { } , Self :: Two { void : ref uninhabited , .. } => match * uninhabited { } } } }"
+        ));
         assert_eq!(
-            output.to_string(),
-            "# [allow (single_use_lifetimes)] impl :: core :: fmt :: Display for EmptyStructType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { :: core :: unreachable ! (\"attempted to format an empty type\") } } # [allow (single_use_lifetimes)] impl :: core :: error :: Error for EmptyStructType { }"
+            expanded_warnings(&derive_input),
+            "const _ : () = { # [deprecated (note = \"`display` attribute is never used, since this type is uninhabited\")] const fn unused_display () { } unused_display () } ;"
+        );
+    }
+
+    #[test]
+    fn infallible_is_only_recognized_by_its_path() {
+        let empty_input: ErrorStackDeriveInput = syn::parse2(quote! {
+            struct EmptyStructType(::std::convert::Infallible);
+        })
+        .expect("malformed test stream");
+        assert!(
+            quote! { #empty_input }
+                .to_string()
+                .contains(":: core :: fmt :: Result { match self . 0 { } } }")
+        );
+
+        for ty in [
+            quote! { io::Infallible },
+            quote! { ::Infallible },
+            quote! { core::convert::Infallible<u8> },
+        ] {
+            let mut derive_input: DeriveInput = syn::parse2(quote! {
+                struct CustomType(#ty);
+            })
+            .expect("malformed test stream");
+            let err = TypeData::new(
+                derive_input.data,
+                &mut derive_input.attrs,
+                &derive_input.ident,
+            )
+            .expect_err(
+                "struct with a lookalike of `Infallible` was parsed successfully as an empty type",
+            );
+            assert_eq!(
+                err.to_string(),
+                "missing `display` attribute for struct with `#[derive(Error)]`"
+            );
+        }
+    }
+
+    #[test]
+    fn padding_fallback_is_only_emitted_with_pad() {
+        let mut derive_input: DeriveInput = syn::parse2(quote! {
+            #[display("{0} rows rejected")]
+            struct CustomType(u32);
+        })
+        .expect("malformed test stream");
+        let type_data = TypeData::new(
+            derive_input.data,
+            &mut derive_input.attrs,
+            &derive_input.ident,
+        )
+        .expect("malformed test stream");
+
+        assert_eq!(
+            type_data.expand(true).to_string(),
+            format!(
+                "{} :: core :: write ! (f , \"{{0}} rows rejected\" , & self . 0)",
+                pad::expand_fallback()
+            )
+        );
+        assert_eq!(
+            type_data.expand(false).to_string(),
+            ":: core :: write ! (f , \"{0} rows rejected\" , & self . 0)"
+        );
+    }
+
+    #[test]
+    fn field_error_attr_rejects_unknown_key() {
+        let mut derive_input: DeriveInput = syn::parse2(quote! {
+            #[display("custom type")]
+            struct CustomType(#[error(uninhabitable)] Void);
+        })
+        .expect("malformed test stream");
+        let err = TypeData::new(
+            derive_input.data,
+            &mut derive_input.attrs,
+            &derive_input.ident,
+        )
+        .expect_err(
+            "stream with unknown field `error` key was parsed successfully as TypeData",
+        );
+        assert_eq!(
+            err.to_string(),
+            "unknown `error` attribute key\nexpected `uninhabited`"
         );
     }
 
@@ -270,7 +727,7 @@ mod tests {
         let err = TypeData::new(
             derive_input.data,
             &mut derive_input.attrs,
-            derive_input.ident.span(),
+            &derive_input.ident,
         )
         .expect_err(
             "stream without display attr was parsed successfully as TypeData",
@@ -289,7 +746,7 @@ mod tests {
         let err = TypeData::new(
             derive_input.data,
             &mut derive_input.attrs,
-            derive_input.ident.span(),
+            &derive_input.ident,
         )
         .expect_err(
             "stream with path display attr was parsed successfully as TypeData",
@@ -300,6 +757,122 @@ mod tests {
         );
     }
 
+    #[test]
+    fn struct_data_rejects_unknown_placeholder_field() {
+        let mut derive_input: DeriveInput = syn::parse2(quote! {
+            #[display("{lenght} and {0}")]
+            struct CustomType {
+                length: usize,
+            }
+        })
+        .expect("malformed test stream");
+        let err = TypeData::new(
+            derive_input.data,
+            &mut derive_input.attrs,
+            &derive_input.ident,
+        )
+        .expect_err(
+            "stream with unknown placeholder field was parsed successfully as TypeData",
+        );
+
+        let messages: Vec<String> =
+            err.into_iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "no field `lenght` on this struct\ndid you mean `length`?",
+                "no field `0` on this struct"
+            ]
+        );
+    }
+
+    #[test]
+    fn enum_data_rejects_unknown_placeholder_field() {
+        let mut derive_input: DeriveInput = syn::parse2(quote! {
+            enum CustomType {
+                #[display("{2} {name}")]
+                Tuple(u8, u8),
+            }
+        })
+        .expect("malformed test stream");
+        let err = TypeData::new(
+            derive_input.data,
+            &mut derive_input.attrs,
+            &derive_input.ident,
+        )
+        .expect_err(
+            "stream with unknown placeholder field was parsed successfully as TypeData",
+        );
+
+        let messages: Vec<String> =
+            err.into_iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "no field `2` on this variant",
+                "no field `name` on this variant"
+            ]
+        );
+    }
+
+    #[test]
+    fn transparent_struct_delegates_to_inner_field() {
+        let derive_input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[display(transparent)]
+            struct CustomType(InnerError);
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_impl(&derive_input, Some("Display")),
+            "# [allow (single_use_lifetimes)] impl :: core :: fmt :: Display for CustomType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { :: core :: fmt :: Display :: fmt (& self . 0 , f) } }"
+        );
+    }
+
+    #[test]
+    fn transparent_struct_requires_single_field() {
+        let mut derive_input: DeriveInput = syn::parse2(quote! {
+            #[display(transparent)]
+            struct CustomType;
+        })
+        .expect("malformed test stream");
+        let err = TypeData::new(
+            derive_input.data,
+            &mut derive_input.attrs,
+            &derive_input.ident,
+        )
+        .expect_err(
+            "transparent struct without fields was parsed successfully as TypeData",
+        );
+        assert_eq!(
+            err.to_string(),
+            "`transparent` requires exactly one field, but this struct has none"
+        );
+    }
+
+    #[test]
+    fn transparent_variant_requires_single_field() {
+        let mut derive_input: DeriveInput = syn::parse2(quote! {
+            enum CustomType {
+                #[display(transparent)]
+                Pair { first: u8, second: u8 },
+            }
+        })
+        .expect("malformed test stream");
+        let err = TypeData::new(
+            derive_input.data,
+            &mut derive_input.attrs,
+            &derive_input.ident,
+        )
+        .expect_err(
+            "transparent variant with two fields was parsed successfully as TypeData",
+        );
+        assert_eq!(
+            err.to_string(),
+            "`transparent` requires exactly one field, but this variant has 2"
+        );
+    }
+
     #[test]
     fn enum_data_requires_display_attr() {
         let mut derive_input: DeriveInput =
@@ -308,7 +881,7 @@ mod tests {
         let err = TypeData::new(
             derive_input.data,
             &mut derive_input.attrs,
-            derive_input.ident.span(),
+            &derive_input.ident,
         )
         .expect_err(
             "stream without display attr was parsed successfully as TypeData",
@@ -327,7 +900,7 @@ mod tests {
         let err = TypeData::new(
             derive_input.data,
             &mut derive_input.attrs,
-            derive_input.ident.span(),
+            &derive_input.ident,
         )
         .expect_err(
             "stream with path display attr was parsed successfully as TypeData",
@@ -352,7 +925,7 @@ mod tests {
         let err = TypeData::new(
             derive_input.data,
             &mut derive_input.attrs,
-            derive_input.ident.span(),
+            &derive_input.ident,
         )
         .expect_err(
             "stream with path display attr was parsed successfully as TypeData",
@@ -363,6 +936,177 @@ mod tests {
         );
     }
 
+    #[test]
+    fn enum_default_display_is_expanded_per_fallback_variant() {
+        let derive_input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[display("database error in {table}")]
+            #[error(no_context_check)]
+            enum CustomType {
+                #[display("connection lost")]
+                Connection,
+                Query { table: String, query: String },
+                #[cfg(true)]
+                Insert { table: String },
+            }
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_impl(&derive_input, Some("Display")),
+            "# [allow (single_use_lifetimes)] impl :: core :: fmt :: Display for CustomType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { if f . width () . is_some () || f . precision () . is_some () { struct Pad < 'a , 'b > { f : & 'a mut :: core :: fmt :: Formatter < 'b > , buf : [u8 ; 256] , len : usize , chars : usize , spilled : bool , } impl :: core :: fmt :: Write for Pad < '_ , '_ > { fn write_str (& mut self , s : & str) -> :: core :: fmt :: Result { let end = self . f . precision () . map_or (s . len () , | precision | { s . char_indices () . nth (precision . saturating_sub (self . chars)) . map_or (s . len () , | (index , _) | index) }) ; let (head , _) = s . split_at (end) ; self . chars = self . chars . saturating_add (head . chars () . count ()) ; if ! self . spilled { let start = self . len ; self . len = start . saturating_add (head . len ()) ; if let :: core :: option :: Option :: Some (slot) = self . buf . get_mut (start .. self . len) { slot . copy_from_slice (head . as_bytes ()) ; return :: core :: result :: Result :: Ok (()) ; } self . spilled = true ; let buffered = :: core :: str :: from_utf8 (self . buf . get (.. start) . unwrap_or_default () ,) . map_err (| _ | :: core :: fmt :: Error) ? ; self . f . write_str (buffered) ? ; } self . f . write_str (head) } } let alternate = f . alternate () ; let mut pad = Pad { f : & mut * f , buf : [0 ; 256] , len : 0 , chars : 0 , spilled : false , } ; if alternate { :: core :: fmt :: Write :: write_fmt (& mut pad , :: core :: format_args ! (\"{:#}\" , self) ,) ? ; } else { :: core :: fmt :: Write :: write_fmt (& mut pad , :: core :: format_args ! (\"{}\" , self) ,) ? ; } if ! pad . spilled { let message = :: core :: str :: from_utf8 (pad . buf . get (.. pad . len) . unwrap_or_default () ,) . map_err (| _ | :: core :: fmt :: Error) ? ; return pad . f . pad (message) ; } let fill = pad . f . fill () ; let padding = pad . f . width () . map_or (0 , | width | width . saturating_sub (pad . chars)) ; for _ in 0 .. padding { :: core :: fmt :: Write :: write_char (pad . f , fill) ? ; } return :: core :: result :: Result :: Ok (()) ; } match & self { Self :: Connection => f . pad (\"connection lost\") , # [allow (unused_variables)] Self :: Query { table , query } => :: core :: write ! (f , \"database error in {0}\" , table) , # [cfg (true)] # [allow (unused_variables)] Self :: Insert { table } => :: core :: write ! (f , \"database error in {0}\" , table) } } }"
+        );
+    }
+
+    #[test]
+    fn enum_default_display_requires_fields_in_fallback_variants() {
+        let mut derive_input: DeriveInput = syn::parse2(quote! {
+            #[display("database error in {table}")]
+            enum CustomType {
+                Query { table: String },
+                Timeout,
+                Other(String),
+            }
+        })
+        .expect("malformed test stream");
+        let err = TypeData::new(
+            derive_input.data,
+            &mut derive_input.attrs,
+            &derive_input.ident,
+        )
+        .expect_err(
+            "stream with default placeholder missing in a variant was parsed successfully as TypeData",
+        );
+
+        let messages: Vec<String> =
+            err.into_iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "no field `table` on variant `Timeout`",
+                "no field `table` on variant `Other`"
+            ]
+        );
+    }
+
+    #[test]
+    fn enum_auto_display_is_derived_from_variant_idents() {
+        let derive_input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[display(auto, case = "kebab-case", suffix = " error")]
+            enum CustomType {
+                NotFound,
+                #[display("denied")]
+                PermissionDenied,
+                #[display(auto, case = "SCREAMING_SNAKE_CASE", prefix = "E_")]
+                InvalidInput(u8),
+            }
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_impl(&derive_input, Some("Display")),
+            "# [allow (single_use_lifetimes)] impl :: core :: fmt :: Display for CustomType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { match & self { Self :: NotFound => f . pad (\"not-found error\") , Self :: PermissionDenied => f . pad (\"denied\") , Self :: InvalidInput (_field0) => f . pad (\"E_INVALID_INPUT\") } } }"
+        );
+    }
+
+    #[test]
+    fn struct_auto_display_is_rejected() {
+        let mut derive_input: DeriveInput = syn::parse2(quote! {
+            #[display(auto)]
+            struct CustomType;
+        })
+        .expect("malformed test stream");
+        let err = TypeData::new(
+            derive_input.data,
+            &mut derive_input.attrs,
+            &derive_input.ident,
+        )
+        .expect_err("struct with auto display attr was parsed successfully");
+        assert_eq!(
+            err.to_string(),
+            "`auto` is only supported on enums and enum variants"
+        );
+    }
+
+    #[test]
+    fn auto_display_rejects_unknown_case() {
+        let mut derive_input: DeriveInput = syn::parse2(quote! {
+            #[display(auto, case = "camelCase")]
+            enum CustomType { NotFound }
+        })
+        .expect("malformed test stream");
+        let err = TypeData::new(
+            derive_input.data,
+            &mut derive_input.attrs,
+            &derive_input.ident,
+        )
+        .expect_err("enum with unknown case style was parsed successfully");
+        assert_eq!(
+            err.to_string(),
+            "unknown case style\nexpected `lower case`, `Sentence case`, `snake_case`, `kebab-case` or `SCREAMING_SNAKE_CASE`"
+        );
+    }
+
+    #[test]
+    fn doc_display_requires_doc_comment_on_struct() {
+        let mut derive_input: DeriveInput = syn::parse2(quote! {
+            #[display(doc)]
+            struct CustomType;
+        })
+        .expect("malformed test stream");
+        let err = TypeData::new(
+            derive_input.data,
+            &mut derive_input.attrs,
+            &derive_input.ident,
+        )
+        .expect_err("struct without doc comment was parsed successfully");
+        assert_eq!(
+            err.to_string(),
+            "missing doc comment for struct with `#[display(doc)]`"
+        );
+    }
+
+    #[test]
+    fn doc_display_requires_doc_comment_on_fallback_variants() {
+        let mut derive_input: DeriveInput = syn::parse2(quote! {
+            #[display(doc)]
+            enum CustomType {
+                /// documented
+                One,
+                Two,
+            }
+        })
+        .expect("malformed test stream");
+        let err = TypeData::new(
+            derive_input.data,
+            &mut derive_input.attrs,
+            &derive_input.ident,
+        )
+        .expect_err("variant without doc comment was parsed successfully");
+        assert_eq!(
+            err.to_string(),
+            "missing doc comment for variant in enum with `#[display(doc)]`\nadd a doc comment or a `display` attribute to the remaining variants"
+        );
+    }
+
+    #[test]
+    fn doc_display_checks_placeholders() {
+        let mut derive_input: DeriveInput = syn::parse2(quote! {
+            enum CustomType {
+                /// missing {field}
+                #[display(doc)]
+                One { value: u8 },
+            }
+        })
+        .expect("malformed test stream");
+        let err = TypeData::new(
+            derive_input.data,
+            &mut derive_input.attrs,
+            &derive_input.ident,
+        )
+        .expect_err("doc comment with unknown field was parsed successfully");
+        assert_eq!(err.to_string(), "no field `field` on this variant");
+    }
+
     #[test]
     fn union_type_is_rejected() {
         let mut derive_input: DeriveInput =
@@ -371,7 +1115,7 @@ mod tests {
         let err = TypeData::new(
             derive_input.data,
             &mut derive_input.attrs,
-            derive_input.ident.span(),
+            &derive_input.ident,
         )
         .expect_err(
             "stream with union type was parsed successfully as TypeData",
@@ -397,10 +1141,9 @@ mod tests {
         })
         .expect("malformed test stream");
 
-        let output = quote! { #derive_input };
         assert_eq!(
-            output.to_string(),
-            "# [allow (single_use_lifetimes)] impl :: core :: fmt :: Display for CustomType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { match & self { # [cfg (true)] Self :: Two (_field0 , _field1 , _field2 , _field3) => :: core :: write ! (f , \"custom type two {}.{}.{}.{}\" , _field0 , _field1 , _field2 , _field3) , _ => :: core :: write ! (f , \"custom type\") } } } # [allow (single_use_lifetimes)] impl :: core :: error :: Error for CustomType { }"
+            expanded_impl(&derive_input, Some("Display")),
+            "# [allow (single_use_lifetimes)] impl :: core :: fmt :: Display for CustomType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { if f . width () . is_some () || f . precision () . is_some () { struct Pad < 'a , 'b > { f : & 'a mut :: core :: fmt :: Formatter < 'b > , buf : [u8 ; 256] , len : usize , chars : usize , spilled : bool , } impl :: core :: fmt :: Write for Pad < '_ , '_ > { fn write_str (& mut self , s : & str) -> :: core :: fmt :: Result { let end = self . f . precision () . map_or (s . len () , | precision | { s . char_indices () . nth (precision . saturating_sub (self . chars)) . map_or (s . len () , | (index , _) | index) }) ; let (head , _) = s . split_at (end) ; self . chars = self . chars . saturating_add (head . chars () . count ()) ; if ! self . spilled { let start = self . len ; self . len = start . saturating_add (head . len ()) ; if let :: core :: option :: Option :: Some (slot) = self . buf . get_mut (start .. self . len) { slot . copy_from_slice (head . as_bytes ()) ; return :: core :: result :: Result :: Ok (()) ; } self . spilled = true ; let buffered = :: core :: str :: from_utf8 (self . buf . get (.. start) . unwrap_or_default () ,) . map_err (| _ | :: core :: fmt :: Error) ? ; self . f . write_str (buffered) ? ; } self . f . write_str (head) } } let alternate = f . alternate () ; let mut pad = Pad { f : & mut * f , buf : [0 ; 256] , len : 0 , chars : 0 , spilled : false , } ; if alternate { :: core :: fmt :: Write :: write_fmt (& mut pad , :: core :: format_args ! (\"{:#}\" , self) ,) ? ; } else { :: core :: fmt :: Write :: write_fmt (& mut pad , :: core :: format_args ! (\"{}\" , self) ,) ? ; } if ! pad . spilled { let message = :: core :: str :: from_utf8 (pad . buf . get (.. pad . len) . unwrap_or_default () ,) . map_err (| _ | :: core :: fmt :: Error) ? ; return pad . f . pad (message) ; } let fill = pad . f . fill () ; let padding = pad . f . width () . map_or (0 , | width | width . saturating_sub (pad . chars)) ; for _ in 0 .. padding { :: core :: fmt :: Write :: write_char (pad . f , fill) ? ; } return :: core :: result :: Result :: Ok (()) ; } match & self { # [cfg (true)] Self :: Two (_field0 , _field1 , _field2 , _field3) => :: core :: write ! (f , \"custom type two {0}.{1}.{2}.{3}\" , _field0 , _field1 , _field2 , _field3) , _ => f . pad (\"custom type\") } } }"
         );
     }
 }
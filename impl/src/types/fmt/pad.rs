@@ -0,0 +1,124 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::LitStr;
+
+use super::template;
+
+/// Expands to a `write!` of the format string and its arguments. A message
+/// without placeholders skips `fmt::Arguments` and is written with `f.pad`,
+/// which applies the width, fill and precision given by the caller, or with
+/// `f.write_str` without `pad`.
+pub(crate) fn write_message(
+    lit_str: &LitStr,
+    args: &[TokenStream2],
+    pad: bool,
+) -> TokenStream2 {
+    if args.is_empty() {
+        let message = template::unescape_braces(&lit_str.value());
+        return write_literal(&LitStr::new(&message, lit_str.span()), pad);
+    }
+
+    quote! {
+        ::core::write!(f, #lit_str, #(#args),*)
+    }
+}
+
+/// Expands to a write of a message that is not a format string, with `f.pad`
+/// or, without `pad`, with `f.write_str`.
+pub(crate) fn write_literal(message: &LitStr, pad: bool) -> TokenStream2 {
+    if pad {
+        quote! { f.pad(#message) }
+    } else {
+        quote! { f.write_str(#message) }
+    }
+}
+
+/// Expands to the start of a `fmt` body that honors the width and precision
+/// of the caller for messages built from several pieces. Without an allocator
+/// the message is formatted once into a buffer on the stack, cut off at the
+/// precision, and then written with `f.pad`. A message that does not fit in
+/// the buffer is written as it is formatted, with the fill after it.
+pub(crate) fn expand_fallback() -> TokenStream2 {
+    quote! {
+        if f.width().is_some() || f.precision().is_some() {
+            struct Pad<'a, 'b> {
+                f: &'a mut ::core::fmt::Formatter<'b>,
+                buf: [u8; 256],
+                len: usize,
+                chars: usize,
+                spilled: bool,
+            }
+
+            impl ::core::fmt::Write for Pad<'_, '_> {
+                fn write_str(&mut self, s: &str) -> ::core::fmt::Result {
+                    let end = self.f.precision().map_or(s.len(), |precision| {
+                        s.char_indices()
+                            .nth(precision.saturating_sub(self.chars))
+                            .map_or(s.len(), |(index, _)| index)
+                    });
+                    let (head, _) = s.split_at(end);
+                    self.chars = self.chars.saturating_add(head.chars().count());
+
+                    if !self.spilled {
+                        let start = self.len;
+                        self.len = start.saturating_add(head.len());
+                        if let ::core::option::Option::Some(slot) =
+                            self.buf.get_mut(start..self.len)
+                        {
+                            slot.copy_from_slice(head.as_bytes());
+                            return ::core::result::Result::Ok(());
+                        }
+
+                        self.spilled = true;
+                        let buffered = ::core::str::from_utf8(
+                            self.buf.get(..start).unwrap_or_default(),
+                        )
+                        .map_err(|_| ::core::fmt::Error)?;
+                        self.f.write_str(buffered)?;
+                    }
+
+                    self.f.write_str(head)
+                }
+            }
+
+            let alternate = f.alternate();
+            let mut pad = Pad {
+                f: &mut *f,
+                buf: [0; 256],
+                len: 0,
+                chars: 0,
+                spilled: false,
+            };
+            if alternate {
+                ::core::fmt::Write::write_fmt(
+                    &mut pad,
+                    ::core::format_args!("{:#}", self),
+                )?;
+            } else {
+                ::core::fmt::Write::write_fmt(
+                    &mut pad,
+                    ::core::format_args!("{}", self),
+                )?;
+            }
+
+            if !pad.spilled {
+                let message = ::core::str::from_utf8(
+                    pad.buf.get(..pad.len).unwrap_or_default(),
+                )
+                .map_err(|_| ::core::fmt::Error)?;
+                return pad.f.pad(message);
+            }
+
+            let fill = pad.f.fill();
+            let padding = pad
+                .f
+                .width()
+                .map_or(0, |width| width.saturating_sub(pad.chars));
+            for _ in 0..padding {
+                ::core::fmt::Write::write_char(pad.f, fill)?;
+            }
+
+            return ::core::result::Result::Ok(());
+        }
+    }
+}
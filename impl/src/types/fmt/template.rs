@@ -0,0 +1,1923 @@
+#[cfg(test)]
+use std::fmt::{self, Debug, Formatter};
+use std::{iter, mem, ops::Range, str::Chars};
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{ToTokens, quote, quote_spanned};
+use syn::{
+    Expr, ExprLit, Field, Fields, Ident, Index, Lit, LitStr, Member, Token,
+    ext::IdentExt as _,
+    parse::{Parse, ParseStream, Parser as _},
+    spanned::Spanned as _,
+    token::Comma,
+};
+
+use super::bounds::FieldBound;
+
+#[derive(Clone)]
+pub(crate) struct Template {
+    lit_str: LitStr,
+    pieces: Vec<Piece>,
+    args: FormatArgs,
+    owner: Option<Owner>,
+    from_doc: bool,
+    alt: Option<Box<Template>>,
+}
+
+#[cfg(test)]
+impl Debug for Template {
+    fn fmt(&self, _: &mut Formatter<'_>) -> fmt::Result {
+        Ok(())
+    }
+}
+
+impl Template {
+    /// Parses a template along with its `alt = "..."` template, if any,
+    /// which shares the same arguments.
+    pub(crate) fn new(
+        lit_str: LitStr,
+        mut args: FormatArgs,
+    ) -> syn::Result<Self> {
+        let alt = args.alt.take();
+        let mut template = Self::parse(lit_str, args)?;
+        if let Some(alt_lit_str) = alt {
+            template.alt = Some(Box::new(Self::parse(
+                alt_lit_str,
+                template.args.clone(),
+            )?));
+            template.check_alt_refs()?;
+        }
+        template.check_args()?;
+
+        Ok(template)
+    }
+
+    /// Parses a template taken from a doc comment, whose string does not
+    /// match the source code, so errors point to the whole comment.
+    pub(crate) fn from_doc(lit_str: LitStr) -> syn::Result<Self> {
+        let value = lit_str.value();
+        let pieces = TemplateParser {
+            source_offset: None,
+            ..TemplateParser::new(&lit_str, &value)
+        }
+        .parse()?;
+        drop(value);
+
+        let template = Self {
+            from_doc: true,
+            ..Self::from_parts(lit_str, pieces, FormatArgs::default())
+        };
+        template.check_args()?;
+
+        Ok(template)
+    }
+
+    /// Parses a `wrap` template, whose only argument is `{message}`, the
+    /// message of the variant being formatted.
+    pub(crate) fn wrapper(lit_str: LitStr) -> syn::Result<Self> {
+        let args = FormatArgs::message(Expr::Verbatim(TokenStream2::new()));
+        let template = Self::parse(lit_str, args)?;
+        template.check_wrapper_args()?;
+
+        Ok(template)
+    }
+
+    /// Builds the `wrap` template of `#[error(prefix = "...")]`, which writes
+    /// the prefix as it is before the message.
+    pub(crate) fn prefix(lit_str: LitStr) -> Self {
+        let prefix = lit_str.value().replace('{', "{{").replace('}', "}}");
+        let message = Placeholder {
+            arg: ArgRef::Field(
+                Member::Named(Ident::new("message", lit_str.span())).into(),
+            ),
+            spec: FormatSpec::default(),
+        };
+
+        let pieces = (!prefix.is_empty())
+            .then_some(Piece::Text(prefix))
+            .into_iter()
+            .chain(iter::once(Piece::Placeholder(message)))
+            .collect();
+
+        Self::from_parts(
+            lit_str,
+            pieces,
+            FormatArgs::message(Expr::Verbatim(TokenStream2::new())),
+        )
+    }
+
+    fn parse(lit_str: LitStr, args: FormatArgs) -> syn::Result<Self> {
+        let value = lit_str.value();
+        let pieces = TemplateParser::new(&lit_str, &value).parse()?;
+        drop(value);
+
+        Ok(Self::from_parts(lit_str, pieces, args))
+    }
+
+    const fn from_parts(
+        lit_str: LitStr,
+        pieces: Vec<Piece>,
+        args: FormatArgs,
+    ) -> Self {
+        Self {
+            lit_str,
+            pieces,
+            args,
+            owner: None,
+            from_doc: false,
+            alt: None,
+        }
+    }
+
+    fn check_args(&self) -> syn::Result<()> {
+        let mut used = vec![false; self.args.len()];
+        let mut errors = Vec::new();
+
+        // the arguments are shared with the alternate template, so they only
+        // need to be used by one of them
+        let alt_arg_refs = self.alt.iter().flat_map(|alt| alt.arg_refs());
+        for arg in self.arg_refs().chain(alt_arg_refs) {
+            let Resolved::Extra(index, _) = self.resolve_arg(arg, false) else {
+                continue;
+            };
+
+            if let Some(is_used) = used.get_mut(index) {
+                *is_used = true;
+            } else if let ArgRef::Next(_, span) = *arg {
+                let count = match self.args.len() {
+                    0 => String::from("no arguments were given"),
+                    1 => String::from("there is 1 argument"),
+                    len => format!("there are {len} arguments"),
+                };
+
+                errors.push(syn::Error::new(
+                    span,
+                    format!(
+                        "invalid reference to positional argument {index} ({count})"
+                    ),
+                ));
+            }
+        }
+
+        for (index, is_used) in used.into_iter().enumerate() {
+            if is_used {
+                continue;
+            }
+
+            let message = if index < self.args.positional.len() {
+                "argument never used"
+            } else {
+                "named argument never used"
+            };
+
+            if let Some(expr) = self.args.get(index) {
+                errors.push(syn::Error::new_spanned(expr, message));
+            }
+        }
+
+        combine_errors(errors)
+    }
+
+    /// Rejects `{alt}` placeholders next to an alternate template, since
+    /// `alt = "..."` looks like the argument they would refer to.
+    fn check_alt_refs(&self) -> syn::Result<()> {
+        let alt_arg_refs = self.alt.iter().flat_map(|alt| alt.arg_refs());
+        let errors = self.arg_refs().chain(alt_arg_refs).filter_map(|arg| {
+            let ArgRef::Field(ref path) = *arg else {
+                return None;
+            };
+            let Member::Named(ref ident) = path.root else {
+                return None;
+            };
+
+            (ident == "alt").then(|| {
+                syn::Error::new(
+                    ident.span(),
+                    "`alt = \"...\"` is the alternate template, not an argument for `{alt}`\nrename the argument, or use `{r#alt}` to refer to a field named `alt`",
+                )
+            })
+        });
+
+        combine_errors(errors)
+    }
+
+    /// Checks that a `wrap` template uses `{message}`, and that its other
+    /// placeholders are intrinsic, since it is shared by every variant.
+    fn check_wrapper_args(&self) -> syn::Result<()> {
+        let mut has_message = false;
+        let mut errors = Vec::new();
+
+        for piece in &self.pieces {
+            let Piece::Placeholder(ref placeholder) = *piece else {
+                continue;
+            };
+
+            match (&placeholder.arg, self.resolve_arg(&placeholder.arg, false)) {
+                (&ArgRef::Field(_), Resolved::Extra(_, &[])) => {
+                    has_message = true;
+                }
+                (_, Resolved::Intrinsic(..)) => {}
+                (arg, _) => errors.push(syn::Error::new(
+                    arg.span(),
+                    "unexpected placeholder in `wrap` template\nonly `{message}`, `{type}`, `{variant}` and `{module}` are available",
+                )),
+            }
+
+            errors.extend(placeholder.spec.count_arg_refs().map(|arg| {
+                syn::Error::new(
+                    arg.span(),
+                    "`wrap` templates have no arguments for the width or precision",
+                )
+            }));
+        }
+
+        if !has_message {
+            errors.push(syn::Error::new(
+                self.lit_str.span(),
+                "missing `{message}` placeholder in `wrap` template",
+            ));
+        }
+
+        combine_errors(errors)
+    }
+
+    pub(crate) fn resolve(
+        &mut self,
+        fields: &Fields,
+        owner: Owner,
+    ) -> syn::Result<Vec<FieldBound>> {
+        self.check_fields(fields, &owner)?;
+        let mut field_bounds = match self.alt {
+            Some(ref mut alt) => alt.resolve(fields, owner.clone())?,
+            None => Vec::new(),
+        };
+
+        self.owner = Some(owner);
+        field_bounds.extend(self.field_bounds(fields));
+        Ok(field_bounds)
+    }
+
+    fn check_fields(&self, fields: &Fields, owner: &Owner) -> syn::Result<()> {
+        let errors = self.arg_refs().filter_map(|arg| {
+            match self.resolve_arg(arg, false) {
+                Resolved::Field(path, _) => {
+                    check_member(&path.root, fields, &owner.description).err()
+                }
+                Resolved::Intrinsic(Intrinsic::Variant, _)
+                    if owner.variant_ident.is_none() =>
+                {
+                    let ArgRef::Field(ref path) = *arg else {
+                        return None;
+                    };
+
+                    Some(syn::Error::new(
+                        path.root.span(),
+                        "`{variant}` is only available in enum variants\nuse `{r#variant}` to refer to a field named `variant`",
+                    ))
+                }
+                Resolved::Intrinsic(intrinsic, _) => {
+                    let ArgRef::Field(ref path) = *arg else {
+                        return None;
+                    };
+                    let _: &Field = find_field(&path.root, fields)?;
+
+                    let (name, meaning) = match intrinsic {
+                        Intrinsic::Type => ("type", "the type name"),
+                        Intrinsic::Variant => ("variant", "the variant name"),
+                        Intrinsic::Module => ("module", "the module path"),
+                    };
+                    Some(syn::Error::new(
+                        path.root.span(),
+                        format!(
+                            "`{{{name}}}` refers to {meaning}, not the field `{name}`\nuse `{{r#{name}}}` to refer to the field"
+                        ),
+                    ))
+                }
+                Resolved::Extra(..) => None,
+            }
+        });
+
+        combine_errors(errors)
+    }
+
+    fn field_bounds(&self, fields: &Fields) -> Vec<FieldBound> {
+        self.pieces
+            .iter()
+            .filter_map(|piece| {
+                let Piece::Placeholder(ref placeholder) = *piece else {
+                    return None;
+                };
+
+                let Resolved::Field(path, _) =
+                    self.resolve_arg(&placeholder.arg, false)
+                else {
+                    return None;
+                };
+
+                if !path.segments.is_empty() {
+                    return None;
+                }
+
+                let field = find_field(&path.root, fields)?;
+                FieldBound::new(&field.ty, &placeholder.spec.format_trait)
+            })
+            .collect()
+    }
+
+    /// Sets the type that the intrinsic placeholders of a `wrap` template
+    /// refer to.
+    pub(crate) fn set_wrapper_owner(&mut self, type_ident: &Ident) {
+        self.owner = Some(Owner::for_enum(type_ident));
+    }
+
+    /// Returns a copy of a `wrap` template whose `{variant}` refers to
+    /// `variant_ident`.
+    pub(crate) fn for_variant(&self, variant_ident: &Ident) -> Self {
+        let owner = self
+            .owner
+            .as_ref()
+            .map(|owner| Owner::for_variant(&owner.type_ident, variant_ident));
+
+        Self {
+            owner,
+            ..self.clone()
+        }
+    }
+
+    /// Whether `{variant}` appears in the template.
+    pub(crate) fn mentions_variant(&self) -> bool {
+        self.arg_refs().any(|arg| {
+            matches!(
+                self.resolve_arg(arg, false),
+                Resolved::Intrinsic(Intrinsic::Variant, _)
+            )
+        })
+    }
+
+    pub(crate) fn depends_on_variant(&self) -> bool {
+        !self.args.is_empty()
+            || self
+                .alt
+                .as_ref()
+                .is_some_and(|alt| alt.depends_on_variant())
+            || self.arg_refs().any(|arg| {
+                matches!(
+                    self.resolve_arg(arg, false),
+                    Resolved::Field(..)
+                        | Resolved::Intrinsic(Intrinsic::Variant, _)
+                )
+            })
+    }
+
+    /// Returns the message if it has no placeholders, which makes it known at
+    /// compile time. The alternate template is not part of it.
+    pub(crate) fn literal_message(&self) -> Option<LitStr> {
+        let message: String = self
+            .pieces
+            .iter()
+            .map(|piece| match *piece {
+                Piece::Text(ref text) => Some(unescape_braces(text)),
+                Piece::Placeholder(_) => None,
+            })
+            .collect::<Option<_>>()?;
+
+        Some(LitStr::new(&message, self.lit_str.span()))
+    }
+
+    /// Fills in a `wrap` template with a message known at compile time. The
+    /// result is only known at compile time as well if the placeholders have
+    /// no format specs or method calls and `{module}` is not used.
+    pub(crate) fn apply_wrapper(&self, message: &str) -> Option<String> {
+        self.pieces
+            .iter()
+            .map(|piece| match *piece {
+                Piece::Text(ref text) => Some(unescape_braces(text)),
+                Piece::Placeholder(ref placeholder)
+                    if placeholder.spec.is_empty() =>
+                {
+                    match self.resolve_arg(&placeholder.arg, false) {
+                        Resolved::Extra(_, &[]) => Some(String::from(message)),
+                        Resolved::Intrinsic(intrinsic, &[]) => {
+                            self.owner.as_ref()?.intrinsic_name(intrinsic)
+                        }
+                        Resolved::Extra(..)
+                        | Resolved::Intrinsic(..)
+                        | Resolved::Field(..) => None,
+                    }
+                }
+                Piece::Placeholder(_) => None,
+            })
+            .collect()
+    }
+
+    /// Expands a `wrap` template around `message`, which is what `{message}`
+    /// refers to.
+    pub(crate) fn expand_wrapper(
+        &self,
+        message: TokenStream2,
+    ) -> (LitStr, Vec<TokenStream2>) {
+        Self {
+            args: FormatArgs::message(Expr::Verbatim(message)),
+            ..self.clone()
+        }
+        .expand(&FieldAccess::SelfRef)
+    }
+
+    /// Whether the message has no placeholders, in both templates.
+    pub(crate) fn is_literal(&self) -> bool {
+        self.pieces
+            .iter()
+            .all(|piece| matches!(*piece, Piece::Text(_)))
+            && self.alt.as_ref().is_none_or(|alt| alt.is_literal())
+    }
+
+    /// Returns the spans of the placeholders that format a field or an extra
+    /// argument, leaving out the intrinsic placeholders.
+    pub(crate) fn interpolation_spans(&self) -> Vec<Span> {
+        self.arg_refs()
+            .filter_map(|arg| match self.resolve_arg(arg, false) {
+                Resolved::Extra(..) | Resolved::Field(..) => Some(arg.span()),
+                Resolved::Intrinsic(..) => None,
+            })
+            .chain(self.alt.iter().flat_map(|alt| alt.interpolation_spans()))
+            .collect()
+    }
+
+    /// Checks the message against the Rust API guidelines, which ask for
+    /// lowercase messages without trailing punctuation. Messages taken from
+    /// doc comments are not checked, since those follow their own style.
+    pub(crate) fn style_violations(&self) -> Vec<(Span, String)> {
+        if self.from_doc {
+            return Vec::new();
+        }
+
+        let span = self.lit_str.span();
+        let (Some(first), Some(last)) =
+            (self.pieces.first(), self.pieces.last())
+        else {
+            return iter::once((
+                span,
+                String::from("`display` message is empty"),
+            ))
+            .chain(self.alt.iter().flat_map(|alt| alt.style_violations()))
+            .collect();
+        };
+
+        let mut violations = Vec::new();
+        let mut has_outer_whitespace = false;
+
+        if let Piece::Text(ref text) = *first {
+            // a first word with two uppercase letters is an acronym, as in
+            // `HTTP`, but a single capital like `A` or `I` is not
+            let first_word = text.split_whitespace().next().unwrap_or_default();
+            if first_word.starts_with(char::is_uppercase)
+                && first_word.chars().filter(|ch| ch.is_uppercase()).count() < 2
+            {
+                violations.push(
+                    "`display` message should start with a lowercase letter",
+                );
+            }
+
+            has_outer_whitespace |= text.starts_with(char::is_whitespace);
+        }
+
+        if let Piece::Text(ref text) = *last {
+            if text.trim_end().ends_with('.') {
+                violations
+                    .push("`display` message should not end with a period");
+            }
+
+            has_outer_whitespace |= text.ends_with(char::is_whitespace);
+        }
+
+        if has_outer_whitespace {
+            violations.push(
+                "`display` message should not start or end with whitespace",
+            );
+        }
+
+        violations
+            .into_iter()
+            .map(|message| (span, String::from(message)))
+            .chain(self.alt.iter().flat_map(|alt| alt.style_violations()))
+            .collect()
+    }
+
+    fn arg_refs(&self) -> impl Iterator<Item = &ArgRef> {
+        self.pieces.iter().flat_map(|piece| {
+            let mut refs = Vec::new();
+            if let Piece::Placeholder(ref placeholder) = *piece {
+                refs.push(&placeholder.arg);
+                refs.extend(placeholder.spec.count_arg_refs());
+            }
+
+            refs
+        })
+    }
+
+    fn resolve_arg<'a>(
+        &'a self,
+        arg: &'a ArgRef,
+        as_count: bool,
+    ) -> Resolved<'a> {
+        match *arg {
+            ArgRef::Next(index, _) => Resolved::Extra(index, &[]),
+            ArgRef::Field(ref path) => {
+                if let Member::Named(ref ident) = path.root
+                    && let Some(index) = self.args.named_index(ident)
+                {
+                    return Resolved::Extra(index, &path.segments);
+                }
+
+                if let Some(intrinsic) = Intrinsic::from_member(&path.root) {
+                    return Resolved::Intrinsic(intrinsic, &path.segments);
+                }
+
+                Resolved::Field(path, as_count)
+            }
+        }
+    }
+
+    pub(crate) fn expand(
+        &self,
+        access: &FieldAccess,
+    ) -> (LitStr, Vec<TokenStream2>) {
+        let mut args: Vec<Resolved<'_>> = Vec::new();
+        let mut index_of = |arg, as_count| {
+            let resolved = self.resolve_arg(arg, as_count);
+            args.iter()
+                .position(|other| *other == resolved)
+                .unwrap_or_else(|| {
+                    args.push(resolved);
+                    args.len().saturating_sub(1)
+                })
+        };
+
+        let mut fmt_string = String::new();
+        for piece in &self.pieces {
+            match *piece {
+                Piece::Text(ref text) => fmt_string.push_str(text),
+                Piece::Placeholder(ref placeholder) => {
+                    let index = index_of(&placeholder.arg, false);
+                    let spec = &placeholder.spec;
+
+                    fmt_string.push('{');
+                    fmt_string.push_str(&index.to_string());
+
+                    if !spec.is_empty() {
+                        fmt_string.push(':');
+                        fmt_string.push_str(&spec.flags);
+
+                        if let Some(ref width) = spec.width {
+                            fmt_string.push_str(&width.render(&mut index_of));
+                        }
+
+                        if let Some(ref precision) = spec.precision {
+                            fmt_string.push('.');
+                            fmt_string
+                                .push_str(&precision.render(&mut index_of));
+                        }
+
+                        fmt_string.push_str(&spec.format_trait);
+                    }
+
+                    fmt_string.push('}');
+                }
+            }
+        }
+
+        let lit_str = LitStr::new(&fmt_string, self.lit_str.span());
+        drop(fmt_string);
+
+        let arg_exprs = args
+            .into_iter()
+            .map(|resolved| match resolved {
+                Resolved::Extra(index, segments) => {
+                    let expr = self.args.get(index);
+                    if segments.is_empty() {
+                        expr.to_token_stream()
+                    } else {
+                        quote! { (#expr) #(#segments)* }
+                    }
+                }
+                Resolved::Intrinsic(intrinsic, segments) => {
+                    let expr = self
+                        .owner
+                        .as_ref()
+                        .map_or_else(TokenStream2::new, |owner| {
+                            owner.intrinsic_expr(intrinsic)
+                        });
+                    if segments.is_empty() {
+                        expr
+                    } else {
+                        quote! { (#expr) #(#segments)* }
+                    }
+                }
+                Resolved::Field(path, as_count) => {
+                    access.arg_expr(path, as_count)
+                }
+            })
+            .collect();
+
+        (lit_str, arg_exprs)
+    }
+
+    /// Expands the template with `write`, which receives the format string
+    /// and its arguments. With an alternate template, the output branches on
+    /// `f.alternate()` so that `{:#}` selects it.
+    pub(crate) fn expand_with<F>(
+        &self,
+        access: &FieldAccess,
+        write: F,
+    ) -> TokenStream2
+    where
+        F: Fn(LitStr, Vec<TokenStream2>) -> TokenStream2,
+    {
+        let (lit_str, args) = self.expand(access);
+        let normal = write(lit_str, args);
+
+        let Some(ref alt) = self.alt else {
+            return normal;
+        };
+
+        let (alt_lit_str, alt_args) = alt.expand(access);
+        let alternate = write(alt_lit_str, alt_args);
+
+        quote! {
+            if f.alternate() {
+                #alternate
+            } else {
+                #normal
+            }
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct FormatArgs {
+    positional: Vec<Expr>,
+    named: Vec<NamedArg>,
+    alt: Option<LitStr>,
+}
+
+#[derive(Clone)]
+struct NamedArg {
+    name: Ident,
+    expr: Expr,
+}
+
+impl FormatArgs {
+    fn len(&self) -> usize {
+        self.positional.len().saturating_add(self.named.len())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.positional.is_empty() && self.named.is_empty()
+    }
+
+    fn get(&self, index: usize) -> Option<&Expr> {
+        self.positional.get(index).or_else(|| {
+            let named_index = index.checked_sub(self.positional.len())?;
+            self.named.get(named_index).map(|arg| &arg.expr)
+        })
+    }
+
+    /// The arguments of a `wrap` template, where `{message}` is `expr`.
+    fn message(expr: Expr) -> Self {
+        Self {
+            named: vec![NamedArg {
+                name: Ident::new("message", Span::call_site()),
+                expr,
+            }],
+            ..Self::default()
+        }
+    }
+
+    fn named_index(&self, ident: &Ident) -> Option<usize> {
+        let named_index =
+            self.named.iter().position(|arg| arg.name == *ident)?;
+        named_index.checked_add(self.positional.len())
+    }
+}
+
+impl Parse for FormatArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Self::default();
+
+        while !input.is_empty() {
+            let _: Comma = input.parse()?;
+            if input.is_empty() {
+                break;
+            }
+
+            if input.peek(Ident::peek_any)
+                && input.peek2(Token![=])
+                && !input.peek2(Token![==])
+            {
+                let name = input.call(Ident::parse_any)?;
+                let _: Token![=] = input.parse()?;
+                let expr: Expr = input.parse()?;
+
+                if args.named.iter().any(|arg| arg.name == name) {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        format!("duplicate argument named `{name}`"),
+                    ));
+                }
+
+                // a string literal named `alt` is the alternate template
+                match expr {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(lit_str),
+                        ..
+                    }) if name == "alt" => {
+                        if args.alt.is_some() {
+                            return Err(syn::Error::new(
+                                name.span(),
+                                "duplicate `alt` template",
+                            ));
+                        }
+
+                        args.alt = Some(lit_str);
+                    }
+                    other_expr => {
+                        args.named.push(NamedArg {
+                            name,
+                            expr: other_expr,
+                        });
+                    }
+                }
+            } else {
+                let expr: Expr = input.parse()?;
+                if !args.named.is_empty() {
+                    return Err(syn::Error::new_spanned(
+                        expr,
+                        "positional arguments cannot follow named arguments",
+                    ));
+                }
+
+                args.positional.push(expr);
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Owner {
+    type_ident: Ident,
+    variant_ident: Option<Ident>,
+    description: String,
+}
+
+impl Owner {
+    pub(crate) fn for_struct(type_ident: &Ident) -> Self {
+        Self {
+            type_ident: type_ident.clone(),
+            variant_ident: None,
+            description: String::from("this struct"),
+        }
+    }
+
+    pub(crate) fn for_enum(type_ident: &Ident) -> Self {
+        Self {
+            type_ident: type_ident.clone(),
+            variant_ident: None,
+            description: String::from("this enum"),
+        }
+    }
+
+    pub(crate) fn for_variant(
+        type_ident: &Ident,
+        variant_ident: &Ident,
+    ) -> Self {
+        Self {
+            type_ident: type_ident.clone(),
+            variant_ident: Some(variant_ident.clone()),
+            description: String::from("this variant"),
+        }
+    }
+
+    pub(crate) fn for_fallback_variant(
+        type_ident: &Ident,
+        variant_ident: &Ident,
+    ) -> Self {
+        Self {
+            description: format!("variant `{variant_ident}`"),
+            ..Self::for_variant(type_ident, variant_ident)
+        }
+    }
+
+    pub(crate) fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub(crate) fn variant_ident(&self) -> Option<&Ident> {
+        self.variant_ident.as_ref()
+    }
+
+    /// Returns the name that an intrinsic placeholder prints, if it is known
+    /// at compile time.
+    fn intrinsic_name(&self, intrinsic: Intrinsic) -> Option<String> {
+        let ident = match intrinsic {
+            Intrinsic::Type => &self.type_ident,
+            Intrinsic::Variant => self.variant_ident.as_ref()?,
+            Intrinsic::Module => return None,
+        };
+
+        Some(ident.unraw().to_string())
+    }
+
+    fn intrinsic_expr(&self, intrinsic: Intrinsic) -> TokenStream2 {
+        if intrinsic == Intrinsic::Module {
+            return quote! { ::core::module_path!() };
+        }
+
+        self.intrinsic_name(intrinsic)
+            .map(|name| quote! { #name })
+            .unwrap_or_default()
+    }
+}
+
+/// Placeholder names that are resolved by the macro itself instead of
+/// referring to a field. A raw identifier (`{r#variant}`) always refers to the
+/// field.
+#[derive(Clone, Copy, PartialEq)]
+enum Intrinsic {
+    Type,
+    Variant,
+    Module,
+}
+
+impl Intrinsic {
+    fn from_member(member: &Member) -> Option<Self> {
+        let Member::Named(ref ident) = *member else {
+            return None;
+        };
+
+        match ident.to_string().as_str() {
+            "type" => Some(Self::Type),
+            "variant" => Some(Self::Variant),
+            "module" => Some(Self::Module),
+            _ => None,
+        }
+    }
+}
+
+#[derive(PartialEq)]
+enum Resolved<'a> {
+    Extra(usize, &'a [PathSegment]),
+    Intrinsic(Intrinsic, &'a [PathSegment]),
+    Field(&'a FieldPath, bool),
+}
+
+fn combine_errors<I>(errors: I) -> syn::Result<()>
+where
+    I: IntoIterator<Item = syn::Error>,
+{
+    match errors.into_iter().reduce(|mut err, err2| {
+        err.combine(err2);
+        err
+    }) {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+#[derive(Clone)]
+enum Piece {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+#[derive(Clone)]
+struct Placeholder {
+    arg: ArgRef,
+    spec: FormatSpec,
+}
+
+#[derive(Clone, Default)]
+struct FormatSpec {
+    flags: String,
+    width: Option<Count>,
+    precision: Option<Count>,
+    format_trait: String,
+}
+
+impl FormatSpec {
+    fn is_empty(&self) -> bool {
+        self.flags.is_empty()
+            && self.width.is_none()
+            && self.precision.is_none()
+            && self.format_trait.is_empty()
+    }
+
+    fn count_arg_refs(&self) -> impl Iterator<Item = &ArgRef> {
+        [&self.width, &self.precision]
+            .into_iter()
+            .flatten()
+            .filter_map(|count| {
+                if let Count::Arg(ref arg) = *count {
+                    Some(arg)
+                } else {
+                    None
+                }
+            })
+    }
+}
+
+#[derive(Clone)]
+enum Count {
+    Literal(String),
+    Arg(ArgRef),
+}
+
+impl Count {
+    fn render<'a, F>(&'a self, index_of: &mut F) -> String
+    where
+        F: FnMut(&'a ArgRef, bool) -> usize,
+    {
+        match *self {
+            Self::Literal(ref literal) => literal.clone(),
+            Self::Arg(ref arg) => format!("{}$", index_of(arg, true)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) enum ArgRef {
+    Next(usize, Span),
+    Field(FieldPath),
+}
+
+impl ArgRef {
+    fn span(&self) -> Span {
+        match *self {
+            Self::Next(_, span) => span,
+            Self::Field(ref path) => path.root.span(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct FieldPath {
+    root: Member,
+    segments: Vec<PathSegment>,
+}
+
+impl From<Member> for FieldPath {
+    fn from(root: Member) -> Self {
+        Self {
+            root,
+            segments: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct PathSegment {
+    member: Member,
+    is_method_call: bool,
+}
+
+impl ToTokens for PathSegment {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let member = &self.member;
+        tokens.extend(quote! { .#member });
+
+        if self.is_method_call {
+            tokens.extend(quote! { () });
+        }
+    }
+}
+
+pub(crate) enum FieldAccess {
+    SelfRef,
+    Bindings,
+}
+
+impl FieldAccess {
+    fn arg_expr(&self, path: &FieldPath, as_count: bool) -> TokenStream2 {
+        let FieldPath {
+            ref root,
+            ref segments,
+        } = *path;
+
+        let span = Span::call_site().located_at(root.span());
+        match *self {
+            Self::SelfRef if as_count => {
+                quote_spanned! {span=> self.#root #(#segments)* }
+            }
+            Self::SelfRef => quote_spanned! {span=> &self.#root #(#segments)* },
+            Self::Bindings => {
+                let ident = binding_ident(root);
+                match (segments.is_empty(), as_count) {
+                    (true, true) => quote_spanned! {span=> *#ident },
+                    (true, false) => quote! { #ident },
+                    (false, true) => {
+                        quote_spanned! {span=> #ident #(#segments)* }
+                    }
+                    (false, false) => {
+                        quote_spanned! {span=> &#ident #(#segments)* }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Turns the escaped braces of a format string without placeholders back into
+/// the text it prints.
+pub(crate) fn unescape_braces(text: &str) -> String {
+    text.replace("{{", "{").replace("}}", "}")
+}
+
+pub(crate) fn binding_ident(member: &Member) -> Ident {
+    match *member {
+        Member::Named(ref ident) => ident.clone(),
+        Member::Unnamed(ref index) => {
+            let span = Span::call_site().located_at(index.span);
+            Ident::new(&format!("_field{}", index.index), span)
+        }
+    }
+}
+
+pub(crate) fn find_field<'a>(
+    member: &Member,
+    fields: &'a Fields,
+) -> Option<&'a Field> {
+    match *member {
+        Member::Named(ref ident) => fields.iter().find(|field| {
+            field
+                .ident
+                .as_ref()
+                .is_some_and(|field_ident| field_ident.unraw() == ident.unraw())
+        }),
+
+        Member::Unnamed(ref index) => {
+            if !matches!(*fields, Fields::Unnamed(_)) {
+                return None;
+            }
+
+            fields.iter().nth(usize::try_from(index.index).ok()?)
+        }
+    }
+}
+
+fn check_member(
+    member: &Member,
+    fields: &Fields,
+    owner: &str,
+) -> syn::Result<()> {
+    match *member {
+        Member::Named(ref ident) => {
+            let name = ident.unraw().to_string();
+            let field_names = fields
+                .iter()
+                .filter_map(|field| field.ident.as_ref())
+                .map(|field_ident| field_ident.unraw().to_string());
+
+            let mut closest: Option<(usize, String)> = None;
+            for field_name in field_names {
+                if field_name == name {
+                    return Ok(());
+                }
+
+                let distance = edit_distance(&name, &field_name);
+                if closest.as_ref().is_none_or(|&(best, _)| distance < best) {
+                    closest = Some((distance, field_name));
+                }
+            }
+
+            let max_distance = name.chars().count().max(3).div_euclid(3);
+            let message = match closest {
+                Some((distance, field_name)) if distance <= max_distance => {
+                    format!(
+                        "no field `{name}` on {owner}\ndid you mean `{field_name}`?"
+                    )
+                }
+                _ => format!("no field `{name}` on {owner}"),
+            };
+
+            Err(syn::Error::new(ident.span(), message))
+        }
+
+        Member::Unnamed(ref index) => {
+            let in_bounds = usize::try_from(index.index)
+                .is_ok_and(|idx| idx < fields.len());
+            if matches!(*fields, Fields::Unnamed(_)) && in_bounds {
+                return Ok(());
+            }
+
+            Err(syn::Error::new(
+                index.span,
+                format!("no field `{}` on {owner}", index.index),
+            ))
+        }
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let mut prev_row: Vec<usize> = (0..=b.chars().count()).collect();
+
+    for (i, a_ch) in a.chars().enumerate() {
+        let mut row = Vec::with_capacity(prev_row.len());
+        row.push(i.saturating_add(1));
+
+        for ((b_ch, &diagonal), &above) in
+            b.chars().zip(&prev_row).zip(prev_row.iter().skip(1))
+        {
+            let left = row.last().copied().unwrap_or_default();
+            let substitution =
+                diagonal.saturating_add(usize::from(a_ch != b_ch));
+            row.push(
+                substitution
+                    .min(above.saturating_add(1))
+                    .min(left.saturating_add(1)),
+            );
+        }
+
+        prev_row = row;
+    }
+
+    prev_row.last().copied().unwrap_or_default()
+}
+
+struct TemplateParser<'a> {
+    lit_str: &'a LitStr,
+    src: &'a str,
+    source_offset: Option<usize>,
+    chars: Chars<'a>,
+    next_implicit: usize,
+}
+
+impl<'a> TemplateParser<'a> {
+    fn new(lit_str: &'a LitStr, src: &'a str) -> Self {
+        Self {
+            lit_str,
+            src,
+            source_offset: source_offset(lit_str),
+            chars: src.chars(),
+            next_implicit: 0,
+        }
+    }
+
+    fn parse(mut self) -> syn::Result<Vec<Piece>> {
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+
+        while let Some(ch) = self.chars.next() {
+            match ch {
+                '{' if self.eat('{') => text.push_str("{{"),
+                '}' if self.eat('}') => text.push_str("}}"),
+                '{' => {
+                    if !text.is_empty() {
+                        pieces.push(Piece::Text(mem::take(&mut text)));
+                    }
+
+                    pieces.push(Piece::Placeholder(self.placeholder()?));
+                }
+                '}' => {
+                    return Err(self.error_before(
+                        ch,
+                        "invalid format string: unmatched `}` found\nif you intended to print `}`, you can escape it using `}}`",
+                    ));
+                }
+                _ => text.push(ch),
+            }
+        }
+
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text));
+        }
+
+        Ok(pieces)
+    }
+
+    fn placeholder(&mut self) -> syn::Result<Placeholder> {
+        let start = self.pos().saturating_sub(1);
+
+        let explicit_arg = self.argument()?;
+        let spec = if self.eat(':') {
+            self.format_spec()?
+        } else {
+            FormatSpec::default()
+        };
+
+        let arg = explicit_arg.unwrap_or_else(|| self.implicit_arg(start));
+
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some('}') => Ok(Placeholder { arg, spec }),
+            Some(ch) => Err(self.error_before(
+                ch,
+                &format!(
+                    "invalid format string: expected `}}`, found `{ch}`\nif you intended to print `{{`, you can escape it using `{{{{`"
+                ),
+            )),
+            None => Err(syn::Error::new(
+                self.span_of(start..self.pos()),
+                "invalid format string: expected `}` but string was terminated\nif you intended to print `{`, you can escape it using `{{`",
+            )),
+        }
+    }
+
+    fn argument(&mut self) -> syn::Result<Option<ArgRef>> {
+        let Some(root) = self.member()? else {
+            return Ok(None);
+        };
+
+        let mut segments = Vec::new();
+        while self.eat_segment_dot() {
+            let Some(member) = self.member()? else {
+                break;
+            };
+
+            let start = self.pos();
+            let is_method_call = self.eat('(');
+            if is_method_call && !self.eat(')') {
+                return Err(syn::Error::new(
+                    self.span_of(start..self.pos()),
+                    "only method calls without arguments are supported in placeholders",
+                ));
+            }
+
+            if is_method_call && matches!(member, Member::Unnamed(_)) {
+                return Err(syn::Error::new(
+                    member.span(),
+                    "expected a method name before `()`",
+                ));
+            }
+
+            segments.push(PathSegment {
+                member,
+                is_method_call,
+            });
+        }
+
+        Ok(Some(ArgRef::Field(FieldPath { root, segments })))
+    }
+
+    fn member(&mut self) -> syn::Result<Option<Member>> {
+        if let Some(member) = self.index()? {
+            return Ok(Some(member));
+        }
+
+        Ok(self.identifier()?.map(Member::Named))
+    }
+
+    fn eat_segment_dot(&mut self) -> bool {
+        let starts_segment = self.peek() == Some('.')
+            && self
+                .peek_second()
+                .is_some_and(|ch| ch.is_ascii_digit() || is_ident_start(ch));
+
+        if starts_segment {
+            self.bump();
+        }
+
+        starts_segment
+    }
+
+    fn format_spec(&mut self) -> syn::Result<FormatSpec> {
+        let mut spec = FormatSpec::default();
+
+        if let (Some(fill), Some(align @ ('<' | '^' | '>'))) =
+            (self.peek(), self.peek_second())
+        {
+            spec.flags.push(fill);
+            spec.flags.push(align);
+            self.bump();
+            self.bump();
+        } else if let Some(align @ ('<' | '^' | '>')) = self.peek() {
+            spec.flags.push(align);
+            self.bump();
+        }
+
+        for flag in ['+', '-', '#'] {
+            if self.eat(flag) {
+                spec.flags.push(flag);
+            }
+        }
+
+        if self.peek() == Some('0') && self.peek_second() != Some('$') {
+            spec.flags.push('0');
+            self.bump();
+        }
+
+        spec.width = self.count()?;
+
+        if self.eat('.') {
+            let start = self.pos();
+            spec.precision = if self.eat('*') {
+                Some(Count::Arg(self.implicit_arg(start)))
+            } else {
+                Some(self.count()?.ok_or_else(|| {
+                    syn::Error::new(
+                        self.span_of(start.saturating_sub(1)..start),
+                        "invalid format string: expected a precision count after `.`",
+                    )
+                })?)
+            };
+        }
+
+        if self.eat('?') {
+            spec.format_trait.push('?');
+        } else if let Some(ident) = self.identifier()? {
+            spec.format_trait.push_str(&ident.to_string());
+            if self.eat('?') {
+                spec.format_trait.push('?');
+            }
+        }
+
+        Ok(spec)
+    }
+
+    fn count(&mut self) -> syn::Result<Option<Count>> {
+        let start = self.pos();
+        if let Some(digits) = self.digits() {
+            let end = self.pos();
+            if self.eat('$') {
+                return self.index_member(digits, start..end).map(|member| {
+                    Some(Count::Arg(ArgRef::Field(member.into())))
+                });
+            }
+
+            return Ok(Some(Count::Literal(String::from(digits))));
+        }
+
+        let checkpoint = self.chars.clone();
+        if let Some(ident) = self.identifier()? {
+            if self.eat('$') {
+                return Ok(Some(Count::Arg(ArgRef::Field(
+                    Member::Named(ident).into(),
+                ))));
+            }
+
+            self.chars = checkpoint;
+        }
+
+        Ok(None)
+    }
+
+    fn index(&mut self) -> syn::Result<Option<Member>> {
+        let start = self.pos();
+        let Some(digits) = self.digits() else {
+            return Ok(None);
+        };
+
+        self.index_member(digits, start..self.pos()).map(Some)
+    }
+
+    fn index_member(
+        &self,
+        digits: &str,
+        range: Range<usize>,
+    ) -> syn::Result<Member> {
+        let span = self.span_of(range);
+        let index = digits.parse().map_err(|_err| {
+            syn::Error::new(
+                span,
+                format!(
+                    "invalid format string: integer `{digits}` is too large"
+                ),
+            )
+        })?;
+
+        Ok(Member::Unnamed(Index { index, span }))
+    }
+
+    fn digits(&mut self) -> Option<&'a str> {
+        let start = self.pos();
+        while self.peek().is_some_and(|ch| ch.is_ascii_digit()) {
+            self.bump();
+        }
+
+        self.src
+            .get(start..self.pos())
+            .filter(|digits| !digits.is_empty())
+    }
+
+    fn identifier(&mut self) -> syn::Result<Option<Ident>> {
+        let start = self.pos();
+
+        let mut lookahead = self.chars.clone();
+        if lookahead.next() == Some('r')
+            && lookahead.next() == Some('#')
+            && lookahead.clone().next().is_some_and(is_ident_start)
+        {
+            self.chars = lookahead;
+        } else if !self.peek().is_some_and(is_ident_start) {
+            return Ok(None);
+        }
+
+        self.bump();
+        while self.peek().is_some_and(is_ident_continue) {
+            self.bump();
+        }
+
+        let span = self.span_of(start..self.pos());
+        let ident_str = self.src.get(start..self.pos()).unwrap_or_default();
+        let mut ident = Ident::parse_any.parse_str(ident_str).map_err(|_err| {
+            syn::Error::new(
+                span,
+                format!(
+                    "invalid format string: `{ident_str}` is not a valid identifier"
+                ),
+            )
+        })?;
+
+        ident.set_span(span);
+        Ok(Some(ident))
+    }
+
+    fn implicit_arg(&mut self, start: usize) -> ArgRef {
+        let arg =
+            ArgRef::Next(self.next_implicit, self.span_of(start..self.pos()));
+        self.next_implicit = self.next_implicit.saturating_add(1);
+        arg
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.clone().next()
+    }
+
+    fn peek_second(&self) -> Option<char> {
+        self.chars.clone().nth(1)
+    }
+
+    fn bump(&mut self) {
+        let _ = self.chars.next();
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        let mut lookahead = self.chars.clone();
+        if lookahead.next() == Some(expected) {
+            self.chars = lookahead;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn pos(&self) -> usize {
+        self.src.len().saturating_sub(self.chars.as_str().len())
+    }
+
+    fn span_of(&self, range: Range<usize>) -> Span {
+        self.source_offset
+            .and_then(|offset| {
+                self.lit_str.token().subspan(
+                    range.start.saturating_add(offset)
+                        ..range.end.saturating_add(offset),
+                )
+            })
+            .unwrap_or_else(|| self.lit_str.span())
+    }
+
+    fn error_before(&self, ch: char, message: &str) -> syn::Error {
+        let end = self.pos();
+        syn::Error::new(
+            self.span_of(end.saturating_sub(ch.len_utf8())..end),
+            message,
+        )
+    }
+}
+
+fn source_offset(lit_str: &LitStr) -> Option<usize> {
+    let source = lit_str.token().to_string();
+
+    if let Some(raw) = source.strip_prefix('r') {
+        let hashes = raw.chars().take_while(|&ch| ch == '#').count();
+        return hashes.checked_add(2);
+    }
+
+    (source.starts_with('"') && !source.contains('\\')).then_some(1)
+}
+
+fn is_ident_start(ch: char) -> bool {
+    ch == '_' || ch.is_alphabetic()
+}
+
+fn is_ident_continue(ch: char) -> bool {
+    ch == '_' || ch.is_alphanumeric()
+}
+
+#[cfg(test)]
+#[expect(
+    clippy::expect_used,
+    reason = "this is a test module with calls to `.expect()`"
+)]
+mod tests {
+    use super::*;
+
+    fn parse_template(tokens: TokenStream2) -> syn::Result<Template> {
+        let parser = |input: ParseStream| {
+            let lit_str: LitStr = input.parse()?;
+            let args: FormatArgs = input.parse()?;
+            Template::new(lit_str, args)
+        };
+        parser.parse2(tokens)
+    }
+
+    fn expand(src: &str, access: &FieldAccess) -> String {
+        let template =
+            parse_template(quote! { #src }).expect("malformed test template");
+        let (lit_str, args) = template.expand(access);
+        quote! { #lit_str, #(#args),* }.to_string()
+    }
+
+    fn expand_struct(src: &str) -> String {
+        expand(src, &FieldAccess::SelfRef)
+    }
+
+    fn expand_variant(src: &str) -> String {
+        expand(src, &FieldAccess::Bindings)
+    }
+
+    fn parse_err(src: &str) -> String {
+        parse_template(quote! { #src })
+            .expect_err("malformed template was parsed successfully")
+            .to_string()
+    }
+
+    fn style_violations(src: &str) -> Vec<String> {
+        parse_template(quote! { #src })
+            .expect("malformed test template")
+            .style_violations()
+            .into_iter()
+            .map(|(_, message)| message)
+            .collect()
+    }
+
+    #[test]
+    fn style_violations_follow_api_guidelines() {
+        assert!(style_violations("failed to parse config").is_empty());
+        assert!(style_violations("HTTP request failed").is_empty());
+        assert!(style_violations("IOError while reading").is_empty());
+        assert_eq!(
+            style_violations("A connection failed"),
+            ["`display` message should start with a lowercase letter"]
+        );
+        assert_eq!(
+            style_violations("I could not open the file"),
+            ["`display` message should start with a lowercase letter"]
+        );
+        assert_eq!(
+            style_violations("{type} failed, see the logs..."),
+            ["`display` message should not end with a period"]
+        );
+        assert_eq!(
+            style_violations("Failed to parse config."),
+            [
+                "`display` message should start with a lowercase letter",
+                "`display` message should not end with a period",
+            ]
+        );
+        assert_eq!(
+            style_violations(" {0} rows rejected\n"),
+            ["`display` message should not start or end with whitespace"]
+        );
+        assert_eq!(style_violations(""), ["`display` message is empty"]);
+    }
+
+    #[test]
+    fn escaped_braces_are_kept() {
+        assert_eq!(
+            expand_struct("{{name}} is {name}, }}{{"),
+            "\"{{name}} is {0}, }}{{\" , & self . name"
+        );
+    }
+
+    #[test]
+    fn repeated_placeholders_share_an_argument() {
+        assert_eq!(
+            expand_struct("{0} {1:?} {0:>5}"),
+            "\"{0} {1:?} {0:>5}\" , & self . 0 , & self . 1"
+        );
+    }
+
+    #[test]
+    fn full_format_spec_is_kept() {
+        assert_eq!(
+            expand_struct("{value:*^+#012.3e}"),
+            "\"{0:*^+#012.3e}\" , & self . value"
+        );
+    }
+
+    #[test]
+    fn hex_debug_format_trait_is_kept() {
+        assert_eq!(
+            expand_struct("{bytes:#x?} {bytes:X?}"),
+            "\"{0:#x?} {0:X?}\" , & self . bytes"
+        );
+    }
+
+    #[test]
+    fn width_and_precision_args_are_rewritten() {
+        assert_eq!(
+            expand_struct("{0:>1$} {value:.prec$} {value:0$}"),
+            "\"{0:>1$} {2:.3$} {2:4$}\" , & self . 0 , self . 1 , & self . value , self . prec , self . 0"
+        );
+    }
+
+    #[test]
+    fn width_arg_named_like_format_trait_is_rewritten() {
+        assert_eq!(
+            expand_struct("{value:x$} {value:x}"),
+            "\"{0:1$} {0:x}\" , & self . value , self . x"
+        );
+    }
+
+    #[test]
+    fn raw_identifiers_are_supported() {
+        assert_eq!(expand_struct("{r#type}"), "\"{0}\" , & self . r#type");
+    }
+
+    #[test]
+    fn fill_character_may_be_a_format_char() {
+        assert_eq!(
+            expand_struct("{name:}<8} {name::^8}"),
+            "\"{0:}<8} {0::^8}\" , & self . name"
+        );
+    }
+
+    #[test]
+    fn field_paths_are_supported() {
+        assert_eq!(
+            expand_struct(
+                "{req.id} {0.1.name} {items.len()} {items.len():>width$}"
+            ),
+            "\"{0} {1} {2} {2:>3$}\" , & self . req . id , & self . 0 . 1 . name , & self . items . len () , self . width"
+        );
+    }
+
+    #[test]
+    fn variant_field_paths_start_at_bindings() {
+        assert_eq!(
+            expand_variant("{req.id} {0.len()} {0}"),
+            "\"{0} {1} {2}\" , & req . id , & _field0 . len () , _field0"
+        );
+    }
+
+    #[test]
+    fn method_calls_with_args_are_rejected() {
+        assert_eq!(
+            parse_err("{items.get(0)}"),
+            "only method calls without arguments are supported in placeholders"
+        );
+    }
+
+    #[test]
+    fn variant_bindings_are_used() {
+        assert_eq!(
+            expand_variant("{0} {name:1$}"),
+            "\"{0} {1:2$}\" , _field0 , name , * _field1"
+        );
+    }
+
+    #[test]
+    fn trailing_whitespace_is_allowed() {
+        assert_eq!(expand_struct("{name  }"), "\"{0}\" , & self . name");
+    }
+
+    #[test]
+    fn edit_distance_counts_single_char_edits() {
+        assert_eq!(edit_distance("length", "length"), 0);
+        assert_eq!(edit_distance("lenght", "length"), 2);
+        assert_eq!(edit_distance("inner", "iner"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn raw_placeholder_matches_raw_field() {
+        let derive_input: syn::DeriveInput = syn::parse2(quote! {
+            struct CustomType { r#type: u8, r#kind: u8 }
+        })
+        .expect("malformed test stream");
+        let syn::Data::Struct(data) = derive_input.data else {
+            unreachable!("test stream is a struct");
+        };
+
+        let mut template = parse_template(quote! { "{r#type} {kind}" })
+            .expect("malformed test template");
+        let _: Vec<_> = template
+            .resolve(&data.fields, Owner::for_struct(&derive_input.ident))
+            .expect("raw placeholders were rejected");
+    }
+
+    #[test]
+    fn intrinsic_placeholders_expand_to_names() {
+        let derive_input: syn::DeriveInput = syn::parse2(quote! {
+            enum CustomType { Query { name: u8 } }
+        })
+        .expect("malformed test stream");
+        let syn::Data::Enum(data) = derive_input.data else {
+            unreachable!("test stream is an enum");
+        };
+        let variant = data.variants.first().expect("test enum has a variant");
+
+        let mut template = parse_template(quote! {
+            "{type}::{variant} in {module:?}: {name} {type.len()}"
+        })
+        .expect("malformed test template");
+        let field_bounds = template
+            .resolve(
+                &variant.fields,
+                Owner::for_variant(&derive_input.ident, &variant.ident),
+            )
+            .expect("intrinsic placeholders were rejected");
+        assert_eq!(field_bounds.len(), 1);
+
+        let (lit_str, args) = template.expand(&FieldAccess::Bindings);
+        assert_eq!(
+            quote! { #lit_str, #(#args),* }.to_string(),
+            "\"{0}::{1} in {2:?}: {3} {4}\" , \"CustomType\" , \"Query\" , :: core :: module_path ! () , name , (\"CustomType\") . len ()"
+        );
+    }
+
+    #[test]
+    fn variant_placeholder_is_rejected_outside_variants() {
+        let ident = Ident::new("CustomType", Span::call_site());
+        let mut template = parse_template(quote! { "{type} {variant}" })
+            .expect("malformed test template");
+        let err = template
+            .resolve(&Fields::Unit, Owner::for_struct(&ident))
+            .expect_err("`{variant}` was accepted on a struct");
+        assert_eq!(
+            err.to_string(),
+            "`{variant}` is only available in enum variants\nuse `{r#variant}` to refer to a field named `variant`"
+        );
+    }
+
+    #[test]
+    fn intrinsic_placeholders_are_rejected_when_shadowing_fields() {
+        let derive_input: syn::DeriveInput = syn::parse2(quote! {
+            enum CustomType { Load { r#type: bool, module: String, variant: u8 } }
+        })
+        .expect("malformed test stream");
+        let syn::Data::Enum(ref data) = derive_input.data else {
+            unreachable!("test stream is an enum");
+        };
+        let variant = data.variants.first().expect("test enum has a variant");
+
+        let mut template =
+            parse_template(quote! { "{type} {module} {variant}" })
+                .expect("malformed test template");
+        let err = template
+            .resolve(
+                &variant.fields,
+                Owner::for_variant(&derive_input.ident, &variant.ident),
+            )
+            .expect_err("intrinsic placeholders shadowed fields");
+        assert_eq!(
+            err.into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "`{type}` refers to the type name, not the field `type`\nuse `{r#type}` to refer to the field",
+                "`{module}` refers to the module path, not the field `module`\nuse `{r#module}` to refer to the field",
+                "`{variant}` refers to the variant name, not the field `variant`\nuse `{r#variant}` to refer to the field",
+            ]
+        );
+
+        let mut raw_template =
+            parse_template(quote! { "{r#type} {r#module} {r#variant}" })
+                .expect("malformed test template");
+        let _: Vec<_> = raw_template
+            .resolve(
+                &variant.fields,
+                Owner::for_variant(&derive_input.ident, &variant.ident),
+            )
+            .expect("raw placeholders were rejected");
+    }
+
+    #[test]
+    fn extra_args_are_merged_with_fields() {
+        let template = parse_template(quote! {
+            "{} {name} {actual:>0$} {} {0:.*}", self.len(), 3, actual = 5,
+        })
+        .expect("malformed test template");
+        let (lit_str, args) = template.expand(&FieldAccess::SelfRef);
+        assert_eq!(
+            quote! { #lit_str, #(#args),* }.to_string(),
+            "\"{0} {1} {2:>3$} {4} {5:.2$}\" , self . len () , & self . name , 5 , self . 0 , 3 , & self . 0"
+        );
+    }
+
+    #[test]
+    fn alt_template_branches_on_alternate_flag() {
+        let template = parse_template(quote! {
+            "{name} failed", alt = "{name} failed after {} attempts", 3,
+        })
+        .expect("malformed test template");
+        let output =
+            template.expand_with(&FieldAccess::SelfRef, |lit_str, args| {
+                quote! { ::core::write!(f, #lit_str, #(#args),*) }
+            });
+        assert_eq!(
+            output.to_string(),
+            "if f . alternate () { :: core :: write ! (f , \"{0} failed after {1} attempts\" , & self . name , 3) } else { :: core :: write ! (f , \"{0} failed\" , & self . name) }"
+        );
+    }
+
+    #[test]
+    fn duplicate_alt_templates_are_rejected() {
+        let err = parse_template(quote! { "a", alt = "b", alt = "c" })
+            .expect_err("malformed template was parsed successfully");
+        assert_eq!(err.to_string(), "duplicate `alt` template");
+    }
+
+    #[test]
+    fn alt_placeholder_is_rejected_with_alt_template() {
+        let err =
+            parse_template(quote! { "{alt} failed", alt = "retry failed" })
+                .expect_err("ambiguous `alt` template was parsed successfully");
+        assert_eq!(
+            err.to_string(),
+            "`alt = \"...\"` is the alternate template, not an argument for `{alt}`\nrename the argument, or use `{r#alt}` to refer to a field named `alt`"
+        );
+        drop(
+            parse_template(quote! { "{r#alt} failed", alt = "retry failed" })
+                .expect("raw placeholder was rejected"),
+        );
+    }
+
+    #[test]
+    fn implicit_positional_placeholder_requires_arg() {
+        assert_eq!(
+            parse_err("{}"),
+            "invalid reference to positional argument 0 (no arguments were given)"
+        );
+    }
+
+    #[test]
+    fn implicit_precision_requires_arg() {
+        let err = parse_template(quote! { "{:.*}", 1 })
+            .expect_err("malformed template was parsed successfully");
+        assert_eq!(
+            err.to_string(),
+            "invalid reference to positional argument 1 (there is 1 argument)"
+        );
+    }
+
+    #[test]
+    fn unused_args_are_rejected() {
+        let err = parse_template(quote! { "{0}", 1, unused = 2 })
+            .expect_err("malformed template was parsed successfully");
+        let messages: Vec<String> =
+            err.into_iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            ["argument never used", "named argument never used"]
+        );
+    }
+
+    #[test]
+    fn positional_args_after_named_args_are_rejected() {
+        let err = parse_template(quote! { "{a} {}", a = 1, 2 })
+            .expect_err("malformed template was parsed successfully");
+        assert_eq!(
+            err.to_string(),
+            "positional arguments cannot follow named arguments"
+        );
+    }
+
+    #[test]
+    fn duplicate_named_args_are_rejected() {
+        let err = parse_template(quote! { "{a}", a = 1, a = 2 })
+            .expect_err("malformed template was parsed successfully");
+        assert_eq!(err.to_string(), "duplicate argument named `a`");
+    }
+
+    #[test]
+    fn unmatched_closing_brace_is_rejected() {
+        assert_eq!(
+            parse_err("a } b"),
+            "invalid format string: unmatched `}` found\nif you intended to print `}`, you can escape it using `}}`"
+        );
+    }
+
+    #[test]
+    fn unterminated_placeholder_is_rejected() {
+        assert_eq!(
+            parse_err("a {name"),
+            "invalid format string: expected `}` but string was terminated\nif you intended to print `{`, you can escape it using `{{`"
+        );
+    }
+
+    #[test]
+    fn unexpected_char_in_placeholder_is_rejected() {
+        assert_eq!(
+            parse_err("{name!}"),
+            "invalid format string: expected `}`, found `!`\nif you intended to print `{`, you can escape it using `{{`"
+        );
+    }
+}
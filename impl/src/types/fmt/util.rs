@@ -2,11 +2,15 @@ use std::convert::Infallible;
 
 use proc_macro2::Span;
 use syn::{
-    Attribute, Meta, Variant, parse::Parse, punctuated::Punctuated,
-    spanned::Spanned as _, token::Comma,
+    Attribute, Fields, Ident, Meta, Variant, parse::Parse,
+    punctuated::Punctuated, spanned::Spanned as _, token::Comma,
 };
 
-use super::{super::util, ValidVariantState, VariantData, VariantState};
+use super::{
+    super::util, FallbackVariant, TypeData, ValidVariantState, VariantData,
+    VariantFormatInput, VariantState, input::is_doc_display_attr,
+    template::Owner,
+};
 
 pub(crate) fn get_format_input<T>(display_attr: Attribute) -> syn::Result<T>
 where
@@ -51,6 +55,8 @@ where
 
 pub(crate) fn collect_valid_variant_states(
     variants: Punctuated<Variant, Comma>,
+    type_ident: &Ident,
+    uses_docs: bool,
 ) -> Result<Vec<ValidVariantState>, syn::Error> {
     let mut variant_states_iter = variants.into_iter().map(|variant| {
         use VariantState as VS;
@@ -59,25 +65,58 @@ pub(crate) fn collect_valid_variant_states(
         drop(variant.discriminant);
 
         let mut attrs = variant.attrs;
-        let display_attr = util::take_display_attr(&mut attrs);
+        let display_input = match util::take_display_attr(&mut attrs) {
+            Err(err) => Some(Err(err)),
+            Ok(Some(attr)) if is_doc_display_attr(&attr) => {
+                Some(util::doc_paragraph(&attrs).map_or_else(
+                    || {
+                        Err(syn::Error::new(
+                            attr.span(),
+                            "missing doc comment for variant with `#[display(doc)]`",
+                        ))
+                    },
+                    VariantFormatInput::from_doc,
+                ))
+            }
+            Ok(Some(attr)) => {
+                Some(get_format_input::<VariantFormatInput>(attr))
+            }
+            Ok(None) if uses_docs => util::doc_paragraph(&attrs)
+                .map(VariantFormatInput::from_doc),
+            Ok(None) => None,
+        };
 
-        match display_attr {
+        match display_input {
             None => {
-                drop(variant.fields);
-                drop(variant.ident);
-                drop(attrs);
-                drop(display_attr);
+                drop(display_input);
+
+                let cfg_attrs = attrs
+                    .into_iter()
+                    .filter(|attr| attr.path().is_ident("cfg"))
+                    .collect();
 
-                VS::None(variant_span)
+                VS::None(Box::new(FallbackVariant {
+                    span: variant_span,
+                    cfg_attrs,
+                    ident: variant.ident,
+                    fields: variant.fields,
+                }))
             }
 
-            Some(attr) => match get_format_input(attr) {
-                Ok(input) => VS::Valid(VariantData {
+            Some(input_res) => match input_res.and_then(|mut input| {
+                    let field_bounds = input.resolve_fields(
+                        &variant.fields,
+                        Owner::for_variant(type_ident, &variant.ident),
+                    )?;
+                    Ok((input, field_bounds))
+                }) {
+                Ok((input, field_bounds)) => VS::Valid(Box::new(VariantData {
                     other_attrs: attrs,
                     ident: variant.ident,
                     fields: variant.fields,
                     display_input: input,
-                }),
+                    field_bounds,
+                })),
                 Err(err) => VS::Invalid(err),
             },
         }
@@ -88,7 +127,7 @@ pub(crate) fn collect_valid_variant_states(
     while let Some(state) = variant_states_iter.next() {
         use VariantState as VS;
         match state {
-            VS::None(span) => vec.push(VS::None(span)),
+            VS::None(fallback) => vec.push(VS::None(fallback)),
             VS::Valid(data) => vec.push(VS::Valid(data)),
             VS::Invalid(mut err) => {
                 while let Some(VS::Invalid(err2)) = variant_states_iter.next() {
@@ -115,10 +154,62 @@ pub(crate) fn separate_existing_variant_states(
     for state in states_iter {
         use VariantState as VS;
         match state {
-            VS::Valid(data) => valid_variants.push(data),
-            VS::None(span) => none_spans.push(span),
+            VS::Valid(data) => valid_variants.push(*data),
+            VS::None(fallback) => none_spans.push(fallback.span),
         }
     }
 
     (valid_variants, none_spans)
 }
+
+pub(crate) fn apply_default_display_input(
+    mut default_display_input: VariantFormatInput,
+    variant_states: Vec<ValidVariantState>,
+    type_ident: &Ident,
+) -> syn::Result<TypeData> {
+    let depends_on_variant = default_display_input.depends_on_variant();
+    let mut fallback_variants = Vec::new();
+    let mut variant_display_inputs = Vec::new();
+    let mut errors = Vec::new();
+
+    for state in variant_states {
+        use VariantState as VS;
+        match state {
+            VS::Valid(data) => variant_display_inputs.push(*data),
+            VS::None(fallback) => {
+                if !depends_on_variant {
+                    fallback_variants.push(*fallback);
+                    continue;
+                }
+
+                match fallback.with_display_input(
+                    default_display_input.clone(),
+                    type_ident,
+                ) {
+                    Ok(data) => variant_display_inputs.push(data),
+                    Err(err) => errors.push(err),
+                }
+            }
+        }
+    }
+
+    if let Some(err) = errors.into_iter().reduce(|mut err, err2| {
+        err.combine(err2);
+        err
+    }) {
+        return Err(err);
+    }
+
+    if !fallback_variants.is_empty() {
+        let _: Vec<_> = default_display_input
+            .resolve_fields(&Fields::Unit, Owner::for_enum(type_ident))?;
+    }
+
+    Ok(TypeData::Enum {
+        default_display_input: (!fallback_variants.is_empty())
+            .then_some(default_display_input),
+        variant_display_inputs,
+        fallback_variants,
+        wrapper: None,
+    })
+}
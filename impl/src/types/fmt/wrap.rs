@@ -0,0 +1,68 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{Ident, LitStr};
+
+use super::template::Template;
+
+/// An enum-level template that every variant message is written into, from
+/// `#[error(wrap = "...{message}...")]` or `#[error(prefix = "...")]`.
+pub(crate) struct Wrapper {
+    span: Span,
+    template: Box<Template>,
+}
+
+impl Wrapper {
+    pub(crate) fn from_template(lit_str: LitStr) -> syn::Result<Self> {
+        Ok(Self {
+            span: lit_str.span(),
+            template: Box::new(Template::wrapper(lit_str)?),
+        })
+    }
+
+    pub(crate) fn from_prefix(lit_str: LitStr) -> Self {
+        Self {
+            span: lit_str.span(),
+            template: Box::new(Template::prefix(lit_str)),
+        }
+    }
+
+    pub(crate) const fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Sets the type that `{type}` refers to.
+    pub(crate) fn set_type(&mut self, type_ident: &Ident) {
+        self.template.set_wrapper_owner(type_ident);
+    }
+
+    /// Returns the template for one variant, where `{variant}` refers to
+    /// `variant_ident`.
+    pub(crate) fn for_variant(&self, variant_ident: &Ident) -> Self {
+        Self {
+            span: self.span,
+            template: Box::new(self.template.for_variant(variant_ident)),
+        }
+    }
+
+    /// Whether the template uses `{variant}`, so the variants that fall back
+    /// to the enum-level message need a match arm each.
+    pub(crate) fn depends_on_variant(&self) -> bool {
+        self.template.mentions_variant()
+    }
+
+    /// Puts a message that is known at compile time into the template, if the
+    /// result is known at compile time as well.
+    pub(crate) fn apply(&self, message: &LitStr) -> Option<LitStr> {
+        self.template
+            .apply_wrapper(&message.value())
+            .map(|wrapped| LitStr::new(&wrapped, message.span()))
+    }
+
+    /// Writes `message`, which must implement `Display`, into the template.
+    pub(crate) fn write(&self, message: TokenStream2) -> TokenStream2 {
+        let (lit_str, args) = self.template.expand_wrapper(message);
+        quote! {
+            ::core::write!(f, #lit_str, #(#args),*)
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{ToTokens, quote_spanned};
+use syn::{Ident, LitStr};
+
+/// How the violations of a lint on the `display` messages are reported.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl Level {
+    pub(crate) fn from_lit_str(lit_str: &LitStr) -> syn::Result<Self> {
+        Self::from_name(&lit_str.value()).ok_or_else(|| {
+            syn::Error::new(
+                lit_str.span(),
+                "unknown lint level\nexpected `allow`, `warn` or `deny`",
+            )
+        })
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "allow" => Some(Self::Allow),
+            "warn" => Some(Self::Warn),
+            "deny" => Some(Self::Deny),
+            _ => None,
+        }
+    }
+}
+
+/// A lint on the `display` messages, named after the deprecated item that
+/// carries its warnings.
+#[derive(Clone, Copy)]
+pub(crate) struct Lint {
+    name: &'static str,
+    level: Level,
+}
+
+impl Lint {
+    /// Uses the level given on the type. Lints are allowed by default.
+    pub(crate) fn configure(
+        name: &'static str,
+        type_level: Option<Level>,
+    ) -> Self {
+        Self {
+            name,
+            level: type_level.unwrap_or(Level::Allow),
+        }
+    }
+}
+
+/// Warnings found in the input. Procedural macros cannot emit warnings on
+/// stable Rust, so each one is expanded to the use of a deprecated item, whose
+/// deprecation note holds the message.
+#[derive(Default)]
+pub(crate) struct Warnings {
+    warnings: Vec<Warning>,
+}
+
+struct Warning {
+    lint: &'static str,
+    span: Span,
+    message: String,
+}
+
+impl Warnings {
+    /// Reports the `violations` of `lint` as errors or warnings, depending on
+    /// its level.
+    pub(crate) fn report<I>(
+        &mut self,
+        lint: Lint,
+        violations: I,
+    ) -> syn::Result<()>
+    where
+        I: IntoIterator<Item = (Span, String)>,
+    {
+        let violations_iter = violations.into_iter();
+
+        match lint.level {
+            Level::Allow => {
+                drop(violations_iter);
+                Ok(())
+            }
+
+            Level::Warn => {
+                self.warnings.extend(violations_iter.map(|(span, message)| {
+                    Warning {
+                        lint: lint.name,
+                        span,
+                        message,
+                    }
+                }));
+                Ok(())
+            }
+
+            Level::Deny => {
+                match violations_iter
+                    .map(|(span, message)| syn::Error::new(span, message))
+                    .reduce(|mut err, err2| {
+                        err.combine(err2);
+                        err
+                    }) {
+                    Some(err) => Err(err),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl ToTokens for Warnings {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        tokens.extend(self.warnings.iter().map(|warning| {
+            let Warning {
+                lint,
+                span,
+                ref message,
+            } = *warning;
+            // lints are not reported in code from external macros, so the
+            // item needs the span of the user's tokens
+            let item_ident = Ident::new(lint, span);
+
+            quote_spanned! {span=>
+                const _: () = {
+                    #[deprecated(note = #message)]
+                    const fn #item_ident() {}
+                    #item_ident()
+                };
+            }
+        }));
+    }
+}
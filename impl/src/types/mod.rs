@@ -1,50 +1,134 @@
+#[cfg(test)]
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{ToTokens, quote};
 use syn::{
-    Attribute, DeriveInput, Generics, Ident,
+    Attribute, DeriveInput, Generics, Ident, Visibility,
     parse::{Parse, ParseStream},
 };
 
+mod attr;
+use attr::ContainerAttr;
+
+mod bounds;
+use bounds::Bounds;
+
+mod context;
+use context::ContextAssertion;
+
+mod debug;
+use debug::DebugImpl;
+
 mod fmt;
 use fmt::TypeData;
 
+mod lint;
+use lint::{Level, Lint, Warnings};
+
 mod util;
 use util::ReducedGenerics;
 
 pub(crate) struct ErrorStackDeriveInput {
     other_attrs: Vec<Attribute>,
+    vis: Visibility,
     ident: Ident,
     generics: Generics,
+    bounds: Bounds,
+    context_assertion: ContextAssertion,
+    debug_impl: DebugImpl,
     display_data: TypeData,
+    pad: bool,
+    message_fn: bool,
+    warnings: Warnings,
+}
+
+#[cfg(test)]
+impl Debug for ErrorStackDeriveInput {
+    fn fmt(&self, _: &mut Formatter<'_>) -> FmtResult {
+        Ok(())
+    }
 }
 
 impl Parse for ErrorStackDeriveInput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let derive_input: DeriveInput = input.parse()?;
-
-        drop(derive_input.vis);
+        let mut derive_input: DeriveInput = input.parse()?;
+        attr::expand_namespaced_attrs(&mut derive_input)?;
+        attr::reject_stray_display_attrs(&derive_input)?;
 
         let mut attrs = derive_input.attrs;
+        let container_attr = ContainerAttr::take(&mut attrs)?;
 
-        let display_data = TypeData::new(
-            derive_input.data,
-            &mut attrs,
-            derive_input.ident.span(),
+        let context_assertion = if container_attr.no_context_check() {
+            ContextAssertion::disabled()
+        } else {
+            ContextAssertion::new(&derive_input.data)
+        };
+        let pad = !container_attr.no_padding();
+        let message_fn = container_attr.message_fn();
+        let debug_impl = DebugImpl::new(
+            container_attr.debug(),
+            &derive_input.data,
+            &derive_input.ident,
+        );
+
+        let strict_lint =
+            Lint::configure("field_interpolation", container_attr.strict());
+        let style_lint =
+            Lint::configure("message_style", container_attr.style());
+        let (bound_attr, forward_attrs, enum_wrapper) =
+            container_attr.into_parts();
+
+        let mut display_data =
+            TypeData::new(derive_input.data, &mut attrs, &derive_input.ident)?;
+        display_data.filter_forwarded_attrs(&forward_attrs);
+        if let Some(wrapper) = enum_wrapper {
+            display_data.set_wrapper(wrapper, &derive_input.ident)?;
+        }
+
+        let templates = display_data.templates();
+        let mut warnings = Warnings::default();
+        warnings.report(
+            strict_lint,
+            templates.iter().flat_map(|template| template.interpolation_spans()).map(|span| {
+                (span, String::from("field interpolation is discouraged in `error-stack` contexts\nattach the value to the `Report` with `attach` instead"))
+            }),
+        )?;
+        warnings.report(
+            style_lint,
+            templates
+                .iter()
+                .flat_map(|template| template.style_violations()),
+        )?;
+        drop(templates);
+        warnings.report(
+            Lint::configure("unused_display", Some(Level::Warn)),
+            display_data.unused_display_span().map(|span| {
+                (span, String::from("`display` attribute is never used, since this type is uninhabited"))
+            }),
         )?;
 
         let ident = derive_input.ident;
 
         let mut generics = derive_input.generics;
+        let bounds = Bounds::take(bound_attr, &mut generics)?;
         generics
             .params
             .iter_mut()
             .for_each(util::remove_generic_default);
 
         Ok(Self {
-            other_attrs: attrs,
+            other_attrs: forward_attrs.filter(attrs),
+            vis: derive_input.vis,
             ident,
             generics,
+            bounds,
+            context_assertion,
+            debug_impl,
             display_data,
+            pad,
+            message_fn,
+            warnings,
         })
     }
 }
@@ -53,18 +137,30 @@ impl ToTokens for ErrorStackDeriveInput {
     fn to_tokens(&self, tokens: &mut TokenStream2) {
         let Self {
             ref other_attrs,
+            ref vis,
             ref ident,
             ref generics,
+            ref bounds,
+            ref context_assertion,
+            ref debug_impl,
             ref display_data,
+            pad,
+            message_fn,
+            ref warnings,
         } = *self;
-
-        let where_clause = &generics.where_clause;
-
-        let mut error_trait_generics = generics.clone();
-        error_trait_generics
-            .params
-            .iter_mut()
-            .for_each(util::add_debug_trait_bound);
+        let display_body = display_data.expand(pad);
+        let display_where_clause = util::extend_where_clause(
+            generics,
+            bounds.predicates(display_data.field_bounds(), false),
+        );
+        let error_where_clause = util::extend_where_clause(
+            generics,
+            bounds
+                .predicates(display_data.field_bounds(), true)
+                .into_iter()
+                .chain(debug_impl.error_predicate(generics))
+                .collect(),
+        );
 
         let type_generics: ReducedGenerics = generics
             .params
@@ -73,24 +169,69 @@ impl ToTokens for ErrorStackDeriveInput {
             .map(util::generic_reduced_to_ident)
             .collect();
 
+        let where_clause = &generics.where_clause;
+        let message_impl = message_fn
+            .then(|| display_data.expand_message_fn(vis))
+            .flatten()
+            .map(|message_fn_item| {
+                quote! {
+                    #[allow(single_use_lifetimes, dead_code)]
+                    #(#other_attrs)*
+                    impl #generics #ident #type_generics
+                    #where_clause
+                    {
+                        #message_fn_item
+                    }
+                }
+            });
+
         tokens.extend(quote! {
             #[allow(single_use_lifetimes)]
             #(#other_attrs)*
             impl #generics ::core::fmt::Display for #ident #type_generics
-            #where_clause
+            #display_where_clause
             {
                 fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-                    #display_data
+                    #display_body
                 }
             }
 
-            #[allow(single_use_lifetimes)]
+            #[allow(single_use_lifetimes, unused_lifetimes)]
             #(#other_attrs)*
-            impl #error_trait_generics ::core::error::Error for #ident #type_generics
-            #where_clause
+            impl #generics ::core::error::Error for #ident #type_generics
+            #error_where_clause
             {
             }
+
+            #message_impl
         });
+
+        if let Some(debug_body) = debug_impl.expand_body(ident) {
+            let debug_where_clause = util::extend_where_clause(
+                generics,
+                debug_impl.predicates(
+                    generics,
+                    bounds.predicates(display_data.field_bounds(), false),
+                ),
+            );
+
+            tokens.extend(quote! {
+                #[allow(single_use_lifetimes)]
+                #(#other_attrs)*
+                impl #generics ::core::fmt::Debug for #ident #type_generics
+                #debug_where_clause
+                {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        #debug_body
+                    }
+                }
+            });
+        }
+
+        tokens.extend(debug_impl.expand_check(generics));
+
+        tokens.extend(context_assertion.expand(generics));
+        warnings.to_tokens(tokens);
     }
 }
 
@@ -101,23 +242,104 @@ impl ToTokens for ErrorStackDeriveInput {
 )]
 mod tests {
     use quote::quote;
+    use syn::{File, Item};
 
     use crate::ErrorStackDeriveInput;
 
+    /// Returns the generated `impl` of the trait named `trait_name`, or the
+    /// inherent `impl` for `None`, so that a test only pins the item it covers.
+    pub(super) fn expanded_impl(
+        input: &ErrorStackDeriveInput,
+        trait_name: Option<&str>,
+    ) -> String {
+        expanded_items(input, |item| match *item {
+            Item::Impl(ref item_impl) => {
+                item_impl
+                    .trait_
+                    .as_ref()
+                    .and_then(|trait_path| trait_path.1.segments.last())
+                    .map(|segment| segment.ident.to_string())
+                    .as_deref()
+                    == trait_name
+            }
+            _ => false,
+        })
+    }
+
+    /// Returns the generated `const _` items, which hold the compile-time
+    /// checks and the warnings.
+    pub(super) fn expanded_checks(input: &ErrorStackDeriveInput) -> String {
+        expanded_items(input, |item| matches!(*item, Item::Const(_)))
+    }
+
+    /// Returns the generated uses of deprecated items that carry the warnings.
+    pub(super) fn expanded_warnings(input: &ErrorStackDeriveInput) -> String {
+        expanded_items(input, |item| {
+            matches!(*item, Item::Const(_))
+                && quote! { #item }.to_string().contains("deprecated")
+        })
+    }
+
+    fn expanded_items(
+        input: &ErrorStackDeriveInput,
+        filter: impl Fn(&Item) -> bool,
+    ) -> String {
+        let file: File = syn::parse2(quote! { #input })
+            .expect("expansion is not valid Rust");
+        let items = file.items.iter().filter(|item| filter(item));
+        quote! { #(#items)* }.to_string()
+    }
+
     #[test]
     fn input_works_with_other_attrs() {
         let input: ErrorStackDeriveInput = syn::parse2(quote! {
             #[test_attribute]
             #[display("custom type")]
+            #[error(forward_attrs(test_attribute, test::attribute))]
+            #[test::attribute]
             #[test_attribute_2]
             struct CustomType;
         })
         .expect("malformed test stream");
 
-        let output = quote! { #input };
         assert_eq!(
-            output.to_string(),
-            "# [allow (single_use_lifetimes)] # [test_attribute] # [test_attribute_2] impl :: core :: fmt :: Display for CustomType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { :: core :: write ! (f , \"custom type\" ,) } } # [allow (single_use_lifetimes)] # [test_attribute] # [test_attribute_2] impl :: core :: error :: Error for CustomType { }"
+            expanded_impl(&input, Some("Display")),
+            "# [allow (single_use_lifetimes)] # [test_attribute] # [test :: attribute] impl :: core :: fmt :: Display for CustomType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { f . pad (\"custom type\") } }"
+        );
+        assert_eq!(
+            expanded_impl(&input, Some("Error")),
+            "# [allow (single_use_lifetimes , unused_lifetimes)] # [test_attribute] # [test :: attribute] impl :: core :: error :: Error for CustomType where for < '__error_stack > Self : :: core :: fmt :: Debug { }"
+        );
+    }
+
+    #[test]
+    fn only_cfg_and_lint_attrs_are_forwarded_by_default() {
+        let input: ErrorStackDeriveInput = syn::parse2(quote! {
+            /// Documentation for the type.
+            #[display("custom type")]
+            #[serde(rename_all = "snake_case")]
+            #[repr(u8)]
+            #[cfg(true)]
+            #[cfg_attr(true, allow(dead_code))]
+            #[expect(clippy::pedantic)]
+            #[deny(unused)]
+            enum CustomType {
+                /// Documentation for the variant.
+                #[serde(rename = "first")]
+                #[display("one")]
+                #[warn(unused)]
+                One,
+            }
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_impl(&input, Some("Display")),
+            "# [allow (single_use_lifetimes)] # [cfg (true)] # [cfg_attr (true , allow (dead_code))] # [allow (clippy :: pedantic)] # [deny (unused)] impl :: core :: fmt :: Display for CustomType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { match & self { # [warn (unused)] Self :: One => f . pad (\"one\") } } }"
+        );
+        assert_eq!(
+            expanded_impl(&input, Some("Error")),
+            "# [allow (single_use_lifetimes , unused_lifetimes)] # [cfg (true)] # [cfg_attr (true , allow (dead_code))] # [allow (clippy :: pedantic)] # [deny (unused)] impl :: core :: error :: Error for CustomType where for < '__error_stack > Self : :: core :: fmt :: Debug { }"
         );
     }
 
@@ -131,13 +353,632 @@ mod tests {
         })
         .expect("malformed test stream");
 
-        let output = quote! { #derive_input };
         assert_eq!(
-            output.to_string(),
-            "# [allow (single_use_lifetimes)] impl < # [cfg (true)] T > :: core :: fmt :: Display for CustomType < T > { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { :: core :: write ! (f , \"custom type\" ,) } } # [allow (single_use_lifetimes)] impl < # [cfg (true)] T : :: core :: fmt :: Debug > :: core :: error :: Error for CustomType < T > { }"
+            expanded_impl(&derive_input, Some("Display")),
+            "# [allow (single_use_lifetimes)] impl < # [cfg (true)] T > :: core :: fmt :: Display for CustomType < T > { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { f . pad (\"custom type\") } }"
+        );
+        assert_eq!(
+            expanded_impl(&derive_input, Some("Error")),
+            "# [allow (single_use_lifetimes , unused_lifetimes)] impl < # [cfg (true)] T > :: core :: error :: Error for CustomType < T > where Self : :: core :: fmt :: Debug { }"
+        );
+    }
+
+    #[test]
+    fn bounds_are_inferred_for_interpolated_generic_fields() {
+        let derive_input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[display("{value} {id:x} {value} {count}")]
+            struct CustomType<T: Trait, U> {
+                value: T,
+                id: T::Id,
+                count: usize,
+                _data: PhantomData<U>,
+            }
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_impl(&derive_input, Some("Display")),
+            "# [allow (single_use_lifetimes)] impl < T : Trait , U > :: core :: fmt :: Display for CustomType < T , U > where T : :: core :: fmt :: Display , T :: Id : :: core :: fmt :: LowerHex { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { if f . width () . is_some () || f . precision () . is_some () { struct Pad < 'a , 'b > { f : & 'a mut :: core :: fmt :: Formatter < 'b > , buf : [u8 ; 256] , len : usize , chars : usize , spilled : bool , } impl :: core :: fmt :: Write for Pad < '_ , '_ > { fn write_str (& mut self , s : & str) -> :: core :: fmt :: Result { let end = self . f . precision () . map_or (s . len () , | precision | { s . char_indices () . nth (precision . saturating_sub (self . chars)) . map_or (s . len () , | (index , _) | index) }) ; let (head , _) = s . split_at (end) ; self . chars = self . chars . saturating_add (head . chars () . count ()) ; if ! self . spilled { let start = self . len ; self . len = start . saturating_add (head . len ()) ; if let :: core :: option :: Option :: Some (slot) = self . buf . get_mut (start .. self . len) { slot . copy_from_slice (head . as_bytes ()) ; return :: core :: result :: Result :: Ok (()) ; } self . spilled = true ; let buffered = :: core :: str :: from_utf8 (self . buf . get (.. start) . unwrap_or_default () ,) . map_err (| _ | :: core :: fmt :: Error) ? ; self . f . write_str (buffered) ? ; } self . f . write_str (head) } } let alternate = f . alternate () ; let mut pad = Pad { f : & mut * f , buf : [0 ; 256] , len : 0 , chars : 0 , spilled : false , } ; if alternate { :: core :: fmt :: Write :: write_fmt (& mut pad , :: core :: format_args ! (\"{:#}\" , self) ,) ? ; } else { :: core :: fmt :: Write :: write_fmt (& mut pad , :: core :: format_args ! (\"{}\" , self) ,) ? ; } if ! pad . spilled { let message = :: core :: str :: from_utf8 (pad . buf . get (.. pad . len) . unwrap_or_default () ,) . map_err (| _ | :: core :: fmt :: Error) ? ; return pad . f . pad (message) ; } let fill = pad . f . fill () ; let padding = pad . f . width () . map_or (0 , | width | width . saturating_sub (pad . chars)) ; for _ in 0 .. padding { :: core :: fmt :: Write :: write_char (pad . f , fill) ? ; } return :: core :: result :: Result :: Ok (()) ; } :: core :: write ! (f , \"{0} {1:x} {0} {2}\" , & self . value , & self . id , & self . count) } }"
+        );
+        assert_eq!(
+            expanded_impl(&derive_input, Some("Error")),
+            "# [allow (single_use_lifetimes , unused_lifetimes)] impl < T : Trait , U > :: core :: error :: Error for CustomType < T , U > where T : :: core :: fmt :: Display , T :: Id : :: core :: fmt :: LowerHex , Self : :: core :: fmt :: Debug { }"
+        );
+    }
+
+    #[test]
+    fn container_bound_replaces_inferred_bounds() {
+        let derive_input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[display("{value}")]
+            #[error(bound = "T: Display + Send", add_bound = "T: 'static")]
+            struct CustomType<T> {
+                value: T,
+            }
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_impl(&derive_input, Some("Display")),
+            "# [allow (single_use_lifetimes)] impl < T > :: core :: fmt :: Display for CustomType < T > where T : Display + Send , T : 'static { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { if f . width () . is_some () || f . precision () . is_some () { struct Pad < 'a , 'b > { f : & 'a mut :: core :: fmt :: Formatter < 'b > , buf : [u8 ; 256] , len : usize , chars : usize , spilled : bool , } impl :: core :: fmt :: Write for Pad < '_ , '_ > { fn write_str (& mut self , s : & str) -> :: core :: fmt :: Result { let end = self . f . precision () . map_or (s . len () , | precision | { s . char_indices () . nth (precision . saturating_sub (self . chars)) . map_or (s . len () , | (index , _) | index) }) ; let (head , _) = s . split_at (end) ; self . chars = self . chars . saturating_add (head . chars () . count ()) ; if ! self . spilled { let start = self . len ; self . len = start . saturating_add (head . len ()) ; if let :: core :: option :: Option :: Some (slot) = self . buf . get_mut (start .. self . len) { slot . copy_from_slice (head . as_bytes ()) ; return :: core :: result :: Result :: Ok (()) ; } self . spilled = true ; let buffered = :: core :: str :: from_utf8 (self . buf . get (.. start) . unwrap_or_default () ,) . map_err (| _ | :: core :: fmt :: Error) ? ; self . f . write_str (buffered) ? ; } self . f . write_str (head) } } let alternate = f . alternate () ; let mut pad = Pad { f : & mut * f , buf : [0 ; 256] , len : 0 , chars : 0 , spilled : false , } ; if alternate { :: core :: fmt :: Write :: write_fmt (& mut pad , :: core :: format_args ! (\"{:#}\" , self) ,) ? ; } else { :: core :: fmt :: Write :: write_fmt (& mut pad , :: core :: format_args ! (\"{}\" , self) ,) ? ; } if ! pad . spilled { let message = :: core :: str :: from_utf8 (pad . buf . get (.. pad . len) . unwrap_or_default () ,) . map_err (| _ | :: core :: fmt :: Error) ? ; return pad . f . pad (message) ; } let fill = pad . f . fill () ; let padding = pad . f . width () . map_or (0 , | width | width . saturating_sub (pad . chars)) ; for _ in 0 .. padding { :: core :: fmt :: Write :: write_char (pad . f , fill) ? ; } return :: core :: result :: Result :: Ok (()) ; } :: core :: write ! (f , \"{0}\" , & self . value) } }"
+        );
+        assert_eq!(
+            expanded_impl(&derive_input, Some("Error")),
+            "# [allow (single_use_lifetimes , unused_lifetimes)] impl < T > :: core :: error :: Error for CustomType < T > where T : Display + Send , T : 'static { }"
+        );
+    }
+
+    #[test]
+    fn param_bound_replaces_inferred_bounds_for_param() {
+        let derive_input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[display("{value} {other}")]
+            struct CustomType<#[error(bound = "Display + Sync")] T, U> {
+                value: T,
+                other: U,
+            }
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_impl(&derive_input, Some("Display")),
+            "# [allow (single_use_lifetimes)] impl < T , U > :: core :: fmt :: Display for CustomType < T , U > where U : :: core :: fmt :: Display , T : Display + Sync { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { if f . width () . is_some () || f . precision () . is_some () { struct Pad < 'a , 'b > { f : & 'a mut :: core :: fmt :: Formatter < 'b > , buf : [u8 ; 256] , len : usize , chars : usize , spilled : bool , } impl :: core :: fmt :: Write for Pad < '_ , '_ > { fn write_str (& mut self , s : & str) -> :: core :: fmt :: Result { let end = self . f . precision () . map_or (s . len () , | precision | { s . char_indices () . nth (precision . saturating_sub (self . chars)) . map_or (s . len () , | (index , _) | index) }) ; let (head , _) = s . split_at (end) ; self . chars = self . chars . saturating_add (head . chars () . count ()) ; if ! self . spilled { let start = self . len ; self . len = start . saturating_add (head . len ()) ; if let :: core :: option :: Option :: Some (slot) = self . buf . get_mut (start .. self . len) { slot . copy_from_slice (head . as_bytes ()) ; return :: core :: result :: Result :: Ok (()) ; } self . spilled = true ; let buffered = :: core :: str :: from_utf8 (self . buf . get (.. start) . unwrap_or_default () ,) . map_err (| _ | :: core :: fmt :: Error) ? ; self . f . write_str (buffered) ? ; } self . f . write_str (head) } } let alternate = f . alternate () ; let mut pad = Pad { f : & mut * f , buf : [0 ; 256] , len : 0 , chars : 0 , spilled : false , } ; if alternate { :: core :: fmt :: Write :: write_fmt (& mut pad , :: core :: format_args ! (\"{:#}\" , self) ,) ? ; } else { :: core :: fmt :: Write :: write_fmt (& mut pad , :: core :: format_args ! (\"{}\" , self) ,) ? ; } if ! pad . spilled { let message = :: core :: str :: from_utf8 (pad . buf . get (.. pad . len) . unwrap_or_default () ,) . map_err (| _ | :: core :: fmt :: Error) ? ; return pad . f . pad (message) ; } let fill = pad . f . fill () ; let padding = pad . f . width () . map_or (0 , | width | width . saturating_sub (pad . chars)) ; for _ in 0 .. padding { :: core :: fmt :: Write :: write_char (pad . f , fill) ? ; } return :: core :: result :: Result :: Ok (()) ; } :: core :: write ! (f , \"{0} {1}\" , & self . value , & self . other) } }"
+        );
+        assert_eq!(
+            expanded_impl(&derive_input, Some("Error")),
+            "# [allow (single_use_lifetimes , unused_lifetimes)] impl < T , U > :: core :: error :: Error for CustomType < T , U > where U : :: core :: fmt :: Display , Self : :: core :: fmt :: Debug , T : Display + Sync { }"
+        );
+    }
+
+    #[test]
+    fn error_attr_rejects_unknown_key() {
+        let err = syn::parse2::<ErrorStackDeriveInput>(quote! {
+            #[display("custom type")]
+            #[error(bounds = "T: Send")]
+            struct CustomType<T>(T);
+        })
+        .expect_err("stream with unknown `error` key was parsed successfully");
+        assert_eq!(
+            err.to_string(),
+            "unknown `error` attribute key\nexpected `bound`, `add_bound`, `no_context_check`, `no_padding`, `message_fn`, `forward_attrs`, `strict`, `style`, `wrap`, `prefix` or `debug`"
+        );
+    }
+
+    #[test]
+    fn namespaced_attr_matches_short_forms() {
+        let namespaced: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[error_stack(display = "custom type", add_bound = "T: Send")]
+            enum CustomType<#[error_stack(bound = "Clone")] T> {
+                #[error_stack(transparent)]
+                One(T),
+                #[error_stack(display("{} {0}", 5))]
+                Two(u8),
+                #[error_stack(auto(case = "snake_case"))]
+                Three,
+            }
+        })
+        .expect("malformed namespaced test stream");
+        let short: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[display("custom type")]
+            #[error(add_bound = "T: Send")]
+            enum CustomType<#[error(bound = "Clone")] T> {
+                #[display(transparent)]
+                One(T),
+                #[display("{} {0}", 5)]
+                Two(u8),
+                #[display(auto, case = "snake_case")]
+                Three,
+            }
+        })
+        .expect("malformed short test stream");
+
+        assert_eq!(
+            quote! { #namespaced }.to_string(),
+            quote! { #short }.to_string()
+        );
+    }
+
+    #[test]
+    fn namespaced_attr_rejects_unknown_key() {
+        let err = syn::parse2::<ErrorStackDeriveInput>(quote! {
+            #[error_stack(display = "custom type", code = 5)]
+            struct CustomType;
+        })
+        .expect_err(
+            "stream with unknown `error_stack` key was parsed successfully",
+        );
+        assert_eq!(
+            err.to_string(),
+            "unknown `error_stack` attribute key\nexpected `display`, `transparent`, `auto`, `doc`, `bound`, `add_bound`, `no_context_check`, `no_padding`, `message_fn`, `forward_attrs`, `strict`, `style`, `wrap`, `prefix`, `debug` or `uninhabited`"
+        );
+    }
+
+    #[test]
+    fn strict_mode_rejects_interpolation() {
+        let err = syn::parse2::<ErrorStackDeriveInput>(quote! {
+            #[error_stack(strict)]
+            #[display("{type}: {0} rows rejected in {}", "table")]
+            struct CustomType(usize);
+        })
+        .expect_err(
+            "stream with interpolation in strict mode was parsed successfully",
+        );
+        assert_eq!(
+            err.to_string(),
+            "field interpolation is discouraged in `error-stack` contexts\nattach the value to the `Report` with `attach` instead"
+        );
+        assert_eq!(err.into_iter().count(), 2);
+    }
+
+    #[test]
+    fn strict_mode_warns_on_interpolation() {
+        let input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[error(strict = "warn", no_context_check)]
+            #[display("{type}: {0} rows rejected")]
+            struct CustomType(usize);
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_warnings(&input),
+            "const _ : () = { # [deprecated (note = \"field interpolation is discouraged in `error-stack` contexts\\nattach the value to the `Report` with `attach` instead\")] const fn field_interpolation () { } field_interpolation () } ;"
+        );
+    }
+
+    #[test]
+    fn style_lint_rejects_capitalized_messages() {
+        let err = syn::parse2::<ErrorStackDeriveInput>(quote! {
+            #[error_stack(style = "deny")]
+            enum CustomType {
+                #[display("Failed to parse config.")]
+                Parse,
+
+                #[display("HTTP request failed")]
+                Request,
+            }
+        })
+        .expect_err("stream with badly styled message was parsed successfully");
+        assert_eq!(
+            err.to_string(),
+            "`display` message should start with a lowercase letter"
+        );
+        assert_eq!(err.into_iter().count(), 2);
+    }
+
+    #[test]
+    fn style_lint_warns_on_trailing_period() {
+        let input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[error(style = "warn")]
+            #[display("invalid config.")]
+            struct CustomType;
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_warnings(&input),
+            "const _ : () = { # [deprecated (note = \"`display` message should not end with a period\")] const fn message_style () { } message_style () } ;"
+        );
+    }
+
+    #[test]
+    fn wrap_template_composes_variant_messages() {
+        let input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[error(wrap = "storage: {message} {{retry}}", no_context_check)]
+            #[display("unknown failure")]
+            enum CustomType {
+                #[display("missing key {0}")]
+                Missing(u32),
+
+                #[display(transparent)]
+                Io(std::io::Error),
+
+                #[display(auto)]
+                TimedOut,
+
+                Other,
+            }
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_impl(&input, Some("Display")),
+            "# [allow (single_use_lifetimes)] impl :: core :: fmt :: Display for CustomType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { if f . width () . is_some () || f . precision () . is_some () { struct Pad < 'a , 'b > { f : & 'a mut :: core :: fmt :: Formatter < 'b > , buf : [u8 ; 256] , len : usize , chars : usize , spilled : bool , } impl :: core :: fmt :: Write for Pad < '_ , '_ > { fn write_str (& mut self , s : & str) -> :: core :: fmt :: Result { let end = self . f . precision () . map_or (s . len () , | precision | { s . char_indices () . nth (precision . saturating_sub (self . chars)) . map_or (s . len () , | (index , _) | index) }) ; let (head , _) = s . split_at (end) ; self . chars = self . chars . saturating_add (head . chars () . count ()) ; if ! self . spilled { let start = self . len ; self . len = start . saturating_add (head . len ()) ; if let :: core :: option :: Option :: Some (slot) = self . buf . get_mut (start .. self . len) { slot . copy_from_slice (head . as_bytes ()) ; return :: core :: result :: Result :: Ok (()) ; } self . spilled = true ; let buffered = :: core :: str :: from_utf8 (self . buf . get (.. start) . unwrap_or_default () ,) . map_err (| _ | :: core :: fmt :: Error) ? ; self . f . write_str (buffered) ? ; } self . f . write_str (head) } } let alternate = f . alternate () ; let mut pad = Pad { f : & mut * f , buf : [0 ; 256] , len : 0 , chars : 0 , spilled : false , } ; if alternate { :: core :: fmt :: Write :: write_fmt (& mut pad , :: core :: format_args ! (\"{:#}\" , self) ,) ? ; } else { :: core :: fmt :: Write :: write_fmt (& mut pad , :: core :: format_args ! (\"{}\" , self) ,) ? ; } if ! pad . spilled { let message = :: core :: str :: from_utf8 (pad . buf . get (.. pad . len) . unwrap_or_default () ,) . map_err (| _ | :: core :: fmt :: Error) ? ; return pad . f . pad (message) ; } let fill = pad . f . fill () ; let padding = pad . f . width () . map_or (0 , | width | width . saturating_sub (pad . chars)) ; for _ in 0 .. padding { :: core :: fmt :: Write :: write_char (pad . f , fill) ? ; } return :: core :: result :: Result :: Ok (()) ; } match & self { Self :: Missing (_field0) => :: core :: write ! (f , \"storage: {0} {{retry}}\" , :: core :: format_args ! (\"missing key {0}\" , _field0)) , Self :: Io (_field0) => :: core :: write ! (f , \"storage: {0} {{retry}}\" , _field0) , Self :: TimedOut => :: core :: write ! (f , \"storage: {0} {{retry}}\" , \"timed out\") , _ => :: core :: write ! (f , \"storage: {0} {{retry}}\" , \"unknown failure\") } } }"
+        );
+    }
+
+    #[test]
+    fn prefix_escapes_braces() {
+        let input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[error(prefix = "{storage} ")]
+            enum CustomType {
+                #[display("missing key")]
+                Missing,
+            }
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_impl(&input, Some("Display")),
+            "# [allow (single_use_lifetimes)] impl :: core :: fmt :: Display for CustomType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { if f . width () . is_some () || f . precision () . is_some () { struct Pad < 'a , 'b > { f : & 'a mut :: core :: fmt :: Formatter < 'b > , buf : [u8 ; 256] , len : usize , chars : usize , spilled : bool , } impl :: core :: fmt :: Write for Pad < '_ , '_ > { fn write_str (& mut self , s : & str) -> :: core :: fmt :: Result { let end = self . f . precision () . map_or (s . len () , | precision | { s . char_indices () . nth (precision . saturating_sub (self . chars)) . map_or (s . len () , | (index , _) | index) }) ; let (head , _) = s . split_at (end) ; self . chars = self . chars . saturating_add (head . chars () . count ()) ; if ! self . spilled { let start = self . len ; self . len = start . saturating_add (head . len ()) ; if let :: core :: option :: Option :: Some (slot) = self . buf . get_mut (start .. self . len) { slot . copy_from_slice (head . as_bytes ()) ; return :: core :: result :: Result :: Ok (()) ; } self . spilled = true ; let buffered = :: core :: str :: from_utf8 (self . buf . get (.. start) . unwrap_or_default () ,) . map_err (| _ | :: core :: fmt :: Error) ? ; self . f . write_str (buffered) ? ; } self . f . write_str (head) } } let alternate = f . alternate () ; let mut pad = Pad { f : & mut * f , buf : [0 ; 256] , len : 0 , chars : 0 , spilled : false , } ; if alternate { :: core :: fmt :: Write :: write_fmt (& mut pad , :: core :: format_args ! (\"{:#}\" , self) ,) ? ; } else { :: core :: fmt :: Write :: write_fmt (& mut pad , :: core :: format_args ! (\"{}\" , self) ,) ? ; } if ! pad . spilled { let message = :: core :: str :: from_utf8 (pad . buf . get (.. pad . len) . unwrap_or_default () ,) . map_err (| _ | :: core :: fmt :: Error) ? ; return pad . f . pad (message) ; } let fill = pad . f . fill () ; let padding = pad . f . width () . map_or (0 , | width | width . saturating_sub (pad . chars)) ; for _ in 0 .. padding { :: core :: fmt :: Write :: write_char (pad . f , fill) ? ; } return :: core :: result :: Result :: Ok (()) ; } match & self { Self :: Missing => :: core :: write ! (f , \"{{storage}} {0}\" , \"missing key\") } } }"
+        );
+    }
+
+    #[test]
+    fn wrap_template_rejects_unknown_placeholder() {
+        let err = syn::parse2::<ErrorStackDeriveInput>(quote! {
+            #[error(wrap = "storage: {key}")]
+            enum CustomType {
+                #[display("missing key")]
+                Missing,
+            }
+        })
+        .expect_err(
+            "stream with unknown wrap placeholder was parsed successfully",
+        );
+        assert_eq!(
+            err.to_string(),
+            "unexpected placeholder in `wrap` template\nonly `{message}`, `{type}`, `{variant}` and `{module}` are available"
+        );
+    }
+
+    #[test]
+    fn wrap_template_rejects_positional_placeholders() {
+        let err = syn::parse2::<ErrorStackDeriveInput>(quote! {
+            #[error(wrap = "storage: {} {message}")]
+            enum CustomType {
+                #[display("missing key")]
+                Missing,
+            }
+        })
+        .expect_err(
+            "stream with positional wrap placeholder was parsed successfully",
+        );
+        assert_eq!(
+            err.to_string(),
+            "unexpected placeholder in `wrap` template\nonly `{message}`, `{type}`, `{variant}` and `{module}` are available"
+        );
+    }
+
+    #[test]
+    fn wrap_template_rejects_count_arguments() {
+        let err = syn::parse2::<ErrorStackDeriveInput>(quote! {
+            #[error(wrap = "storage: {message:>width$}")]
+            enum CustomType {
+                #[display("missing key")]
+                Missing,
+            }
+        })
+        .expect_err("stream with wrap width argument was parsed successfully");
+        assert_eq!(
+            err.to_string(),
+            "`wrap` templates have no arguments for the width or precision"
+        );
+    }
+
+    #[test]
+    fn wrap_template_supports_intrinsics_and_format_specs() {
+        let input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[error(wrap = "{type}::{variant}: {message:>12}", no_padding)]
+            #[display("unknown failure")]
+            enum CustomType {
+                #[display("missing key {0}")]
+                Missing(u32),
+
+                Other,
+            }
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_impl(&input, Some("Display")),
+            "# [allow (single_use_lifetimes)] impl :: core :: fmt :: Display for CustomType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { match & self { Self :: Missing (_field0) => :: core :: write ! (f , \"{0}::{1}: {2:>12}\" , \"CustomType\" , \"Missing\" , :: core :: format_args ! (\"missing key {0}\" , _field0)) , Self :: Other => :: core :: write ! (f , \"{0}::{1}: {2:>12}\" , \"CustomType\" , \"Other\" , \"unknown failure\") } } }"
+        );
+    }
+
+    #[test]
+    fn wrap_template_requires_message_placeholder() {
+        let err = syn::parse2::<ErrorStackDeriveInput>(quote! {
+            #[error(wrap = "storage error")]
+            enum CustomType {
+                #[display("missing key")]
+                Missing,
+            }
+        })
+        .expect_err(
+            "stream without message placeholder was parsed successfully",
+        );
+        assert_eq!(
+            err.to_string(),
+            "missing `{message}` placeholder in `wrap` template"
+        );
+    }
+
+    #[test]
+    fn wrap_and_prefix_are_rejected_together() {
+        let err = syn::parse2::<ErrorStackDeriveInput>(quote! {
+            #[error(prefix = "storage: ", wrap = "storage: {message}")]
+            enum CustomType {
+                #[display("missing key")]
+                Missing,
+            }
+        })
+        .expect_err("stream with both wrap and prefix was parsed successfully");
+        assert_eq!(
+            err.to_string(),
+            "only one of `wrap` and `prefix` can be given"
+        );
+    }
+
+    #[test]
+    fn wrap_is_rejected_on_structs() {
+        let err = syn::parse2::<ErrorStackDeriveInput>(quote! {
+            #[error(prefix = "storage: ")]
+            #[display("missing key")]
+            struct CustomType;
+        })
+        .expect_err("struct with prefix was parsed successfully");
+        assert_eq!(
+            err.to_string(),
+            "`wrap` and `prefix` are only supported on enums"
+        );
+    }
+
+    #[test]
+    fn no_padding_writes_messages_directly() {
+        let input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[error(no_padding)]
+            enum CustomType {
+                #[display("one")]
+                One,
+
+                #[display("{type}::{variant}")]
+                Two,
+            }
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_impl(&input, Some("Display")),
+            "# [allow (single_use_lifetimes)] impl :: core :: fmt :: Display for CustomType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { match & self { Self :: One => f . write_str (\"one\") , Self :: Two => :: core :: write ! (f , \"{0}::{1}\" , \"CustomType\" , \"Two\") } } }"
         );
     }
 
+    #[test]
+    fn message_fn_adds_message_accessor() {
+        let input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[error(message_fn)]
+            pub enum CustomType {
+                #[display("one")]
+                One,
+
+                #[display("two")]
+                Two,
+            }
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_impl(&input, None),
+            "# [allow (single_use_lifetimes , dead_code)] impl CustomType { # [doc = r\" Returns the message of this error.\"] # [must_use] pub const fn message (& self) -> & 'static str { match self { Self :: One { .. } => \"one\" , Self :: Two { .. } => \"two\" } } }"
+        );
+    }
+
+    #[test]
+    fn strict_mode_rejects_unknown_level() {
+        let err = syn::parse2::<ErrorStackDeriveInput>(quote! {
+            #[error(strict = "forbid")]
+            #[display("custom type")]
+            struct CustomType;
+        })
+        .expect_err("stream with unknown lint level was parsed successfully");
+        assert_eq!(
+            err.to_string(),
+            "unknown lint level\nexpected `allow`, `warn` or `deny`"
+        );
+    }
+
+    #[test]
+    fn debug_derive_formats_fields() {
+        let input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[error(debug = "derive")]
+            #[display("custom type")]
+            enum CustomType<T> {
+                Unit,
+                Tuple(T, #[cfg(true)] u8),
+                Named { r#type: &'static str },
+            }
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_impl(&input, Some("Debug")),
+            "# [allow (single_use_lifetimes)] impl < T > :: core :: fmt :: Debug for CustomType < T > where T : :: core :: fmt :: Debug { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { match self { Self :: Unit { } => { f . write_str (\"Unit\") } Self :: Tuple { 0 : __self_0 , # [cfg (true)] 1 : __self_1 } => { let mut debug_builder = f . debug_tuple (\"Tuple\") ; debug_builder . field (__self_0) ; # [cfg (true)] debug_builder . field (__self_1) ; debug_builder . finish () } Self :: Named { r#type : __self_0 } => { let mut debug_builder = f . debug_struct (\"Named\") ; debug_builder . field (\"type\" , __self_0) ; debug_builder . finish () } } } }"
+        );
+        assert_eq!(
+            expanded_impl(&input, Some("Error")),
+            "# [allow (single_use_lifetimes , unused_lifetimes)] impl < T > :: core :: error :: Error for CustomType < T > where Self : :: core :: fmt :: Debug { }"
+        );
+    }
+
+    #[test]
+    fn debug_display_forwards_to_display() {
+        let input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[error_stack(debug = "display")]
+            #[display("custom type {0}")]
+            struct CustomType(u8);
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_impl(&input, Some("Debug")),
+            "# [allow (single_use_lifetimes)] impl :: core :: fmt :: Debug for CustomType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { :: core :: fmt :: Display :: fmt (self , f) } }"
+        );
+    }
+
+    #[test]
+    fn debug_check_is_emitted_for_non_generic_types() {
+        let derive_input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[display("custom type")]
+            struct CustomType<'a>(&'a str);
+        })
+        .expect("malformed test stream");
+
+        assert_eq!(
+            expanded_checks(&derive_input),
+            "# [allow (single_use_lifetimes)] const _ : () = { # [allow (dead_code)] # [diagnostic :: on_unimplemented (message = \"`{Self}` must implement `Debug` to derive `Error`\" , label = \"`Debug` is not implemented for this type\" , note = \"add `#[derive(Debug)]`, or let `Error` implement it with `#[error(debug = \\\"derive\\\")]` or `#[error(debug = \\\"display\\\")]`\")] trait ContextDebug { } # [diagnostic :: do_not_recommend] impl < T : ? :: core :: marker :: Sized + :: core :: fmt :: Debug > ContextDebug for T { } # [allow (dead_code)] struct AssertContextDebug < T : ? :: core :: marker :: Sized + ContextDebug > (:: core :: marker :: PhantomData < T > ,) ; impl < 'a > :: core :: marker :: Unpin for AssertContextDebug < CustomType < 'a > > { } } ; # [allow (dead_code , single_use_lifetimes)] const _ : () = { fn context_must_be_send < T : ? :: core :: marker :: Sized + :: core :: marker :: Send > () { } fn context_must_be_sync < T : ? :: core :: marker :: Sized + :: core :: marker :: Sync > () { } fn context_must_be_static < T : ? :: core :: marker :: Sized + 'static > () { } fn assert_context_fields < 'a > () { { context_must_be_send :: < & 'a str > () ; context_must_be_sync :: < & 'a str > () ; context_must_be_static :: < & 'a str > () ; } } } ;"
+        );
+    }
+
+    #[test]
+    fn debug_check_is_skipped_for_generic_types() {
+        let derive_input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[display("{value}")]
+            #[error(no_context_check)]
+            struct CustomType<T: Entity, U> {
+                id: T::Id,
+                value: U,
+            }
+        })
+        .expect("malformed test stream");
+
+        assert!(!expanded_checks(&derive_input).contains("ContextDebug"));
+        assert_eq!(
+            expanded_impl(&derive_input, Some("Error")),
+            "# [allow (single_use_lifetimes , unused_lifetimes)] impl < T : Entity , U > :: core :: error :: Error for CustomType < T , U > where U : :: core :: fmt :: Display , Self : :: core :: fmt :: Debug { }"
+        );
+    }
+
+    #[test]
+    fn debug_rejects_unknown_mode() {
+        let err = syn::parse2::<ErrorStackDeriveInput>(quote! {
+            #[error(debug = "pretty")]
+            #[display("custom type")]
+            struct CustomType;
+        })
+        .expect_err("stream with unknown debug mode was parsed successfully");
+        assert_eq!(
+            err.to_string(),
+            "unknown `debug` mode\nexpected `derive` or `display`"
+        );
+    }
+
+    #[test]
+    fn debug_is_rejected_twice() {
+        let err = syn::parse2::<ErrorStackDeriveInput>(quote! {
+            #[error(debug = "derive", debug = "display")]
+            #[display("custom type")]
+            struct CustomType;
+        })
+        .expect_err("stream with duplicate debug key was parsed successfully");
+        assert_eq!(err.to_string(), "duplicate `debug` key");
+    }
+
+    #[test]
+    fn duplicate_display_attrs_are_rejected() {
+        let err = syn::parse2::<ErrorStackDeriveInput>(quote! {
+            #[display("custom type")]
+            #[error_stack(display = "custom type again")]
+            enum CustomType {
+                #[display("one")]
+                #[display("one again")]
+                One,
+            }
+        })
+        .expect_err(
+            "stream with duplicate `display` attrs was parsed successfully",
+        );
+        let messages: Vec<String> =
+            err.into_iter().map(|single| single.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "duplicate `display` attribute\nif the attributes are conditional, use mutually exclusive predicates, such as `cfg_attr(unix, ...)` and `cfg_attr(not(unix), ...)`"
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_display_attrs_on_variants_are_rejected() {
+        let err = syn::parse2::<ErrorStackDeriveInput>(quote! {
+            #[display("custom type")]
+            enum CustomType {
+                #[display("one")]
+                #[display("one again")]
+                One,
+                #[display("two")]
+                #[display("two again")]
+                Two,
+            }
+        })
+        .expect_err(
+            "stream with duplicate `display` attrs was parsed successfully",
+        );
+        let messages: Vec<String> =
+            err.into_iter().map(|single| single.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "duplicate `display` attribute\nif the attributes are conditional, use mutually exclusive predicates, such as `cfg_attr(unix, ...)` and `cfg_attr(not(unix), ...)`",
+                "duplicate `display` attribute\nif the attributes are conditional, use mutually exclusive predicates, such as `cfg_attr(unix, ...)` and `cfg_attr(not(unix), ...)`"
+            ]
+        );
+    }
+
+    #[test]
+    fn stray_display_attrs_are_rejected() {
+        let err = syn::parse2::<ErrorStackDeriveInput>(quote! {
+            #[display("custom type")]
+            struct CustomType<#[display("param")] T> {
+                #[display("field")]
+                value: T,
+            }
+        })
+        .expect_err(
+            "stream with stray `display` attrs was parsed successfully",
+        );
+        let messages: Vec<String> =
+            err.into_iter().map(|single| single.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "`display` attributes are not supported on generic parameters",
+                "`display` attributes are not supported on fields\nmove it to the struct or enum variant"
+            ]
+        );
+    }
+
+    #[test]
+    fn context_assertion_checks_non_generic_fields() {
+        let derive_input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[display("custom type")]
+            struct CustomType<'a, T> {
+                name: &'a str,
+                value: T,
+            }
+        })
+        .expect("malformed test stream");
+
+        let output = quote! { #derive_input }.to_string();
+        let assertion = output
+            .rsplit_once("const _ : () = ")
+            .map(|(_, assertion)| assertion)
+            .expect("output does not contain a context assertion");
+        assert_eq!(
+            assertion,
+            "{ fn context_must_be_send < T : ? :: core :: marker :: Sized + :: core :: marker :: Send > () { } fn context_must_be_sync < T : ? :: core :: marker :: Sized + :: core :: marker :: Sync > () { } fn context_must_be_static < T : ? :: core :: marker :: Sized + 'static > () { } fn assert_context_fields < 'a , T > () { { context_must_be_send :: < & 'a str > () ; context_must_be_sync :: < & 'a str > () ; context_must_be_static :: < & 'a str > () ; } } } ;"
+        );
+    }
+
+    #[test]
+    fn context_assertion_can_be_disabled() {
+        let derive_input: ErrorStackDeriveInput = syn::parse2(quote! {
+            #[display("custom type")]
+            #[error(no_context_check)]
+            struct CustomType(Rc<u8>);
+        })
+        .expect("malformed test stream");
+
+        assert!(!expanded_checks(&derive_input).contains("context_must_be"));
+    }
+
     #[test]
     fn output_impl_has_attr_allow_single_use_lifetimes() {
         let input: ErrorStackDeriveInput = syn::parse2(quote! {
@@ -146,10 +987,13 @@ mod tests {
         })
         .expect("malformed test stream");
 
-        let output = quote! { #input };
         assert_eq!(
-            output.to_string(),
-            "# [allow (single_use_lifetimes)] impl :: core :: fmt :: Display for CustomType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { :: core :: write ! (f , \"custom type\" ,) } } # [allow (single_use_lifetimes)] impl :: core :: error :: Error for CustomType { }"
+            expanded_impl(&input, Some("Display")),
+            "# [allow (single_use_lifetimes)] impl :: core :: fmt :: Display for CustomType { fn fmt (& self , f : & mut :: core :: fmt :: Formatter < '_ >) -> :: core :: fmt :: Result { f . pad (\"custom type\") } }"
+        );
+        assert_eq!(
+            expanded_impl(&input, Some("Error")),
+            "# [allow (single_use_lifetimes , unused_lifetimes)] impl :: core :: error :: Error for CustomType where for < '__error_stack > Self : :: core :: fmt :: Debug { }"
         );
     }
 }
@@ -1,11 +1,12 @@
-use proc_macro2::TokenStream as TokenStream2;
+use std::mem;
+
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
 use quote::{ToTokens, quote};
 use syn::{
-    Attribute, GenericParam, Ident, Lifetime, Path, TraitBound,
-    TraitBoundModifier, TypeParamBound,
+    Attribute, Expr, ExprLit, GenericParam, Generics, Ident, Lifetime, Lit,
+    LitStr, Meta, WhereClause, WherePredicate,
     punctuated::Punctuated,
-    spanned::Spanned as _,
-    token::{Colon, Comma},
+    token::{Comma, Where},
 };
 
 pub(crate) enum ReducedGenericParam {
@@ -48,11 +49,93 @@ impl ToTokens for ReducedGenerics {
 
 pub(crate) fn take_display_attr(
     attrs: &mut Vec<Attribute>,
-) -> Option<Attribute> {
-    let index = attrs
-        .iter_mut()
-        .position(|attr| attr.path().is_ident("display"))?;
-    Some(attrs.remove(index))
+) -> syn::Result<Option<Attribute>> {
+    let (display_attrs, other_attrs): (Vec<_>, Vec<_>) = mem::take(attrs)
+        .into_iter()
+        .partition(|attr| attr.path().is_ident("display"));
+    *attrs = other_attrs;
+
+    let mut display_attrs_iter = display_attrs.into_iter();
+    let display_attr = display_attrs_iter.next();
+
+    // `cfg_attr` is expanded before the derive runs, so conditional
+    // attributes whose predicates overlap show up here as duplicates too.
+    match display_attrs_iter
+        .map(|attr| {
+            syn::Error::new_spanned(
+                attr,
+                "duplicate `display` attribute\nif the attributes are conditional, use mutually exclusive predicates, such as `cfg_attr(unix, ...)` and `cfg_attr(not(unix), ...)`",
+            )
+        })
+        .reduce(|mut err, err2| {
+            err.combine(err2);
+            err
+        }) {
+        Some(err) => Err(err),
+        None => Ok(display_attr),
+    }
+}
+
+/// Returns the first paragraph of the doc comments in `attrs`, with its lines
+/// trimmed and joined by spaces.
+pub(crate) fn doc_paragraph(attrs: &[Attribute]) -> Option<LitStr> {
+    let mut first_span = None;
+    let mut doc_lines = Vec::new();
+
+    for attr in attrs {
+        let Meta::NameValue(ref meta) = attr.meta else {
+            continue;
+        };
+
+        let Expr::Lit(ExprLit {
+            lit: Lit::Str(ref lit_str),
+            ..
+        }) = meta.value
+        else {
+            continue;
+        };
+
+        if !meta.path.is_ident("doc") {
+            continue;
+        }
+
+        if first_span.is_none() {
+            first_span = Some(lit_str.span());
+        }
+
+        doc_lines.extend(
+            lit_str
+                .value()
+                .split('\n')
+                .map(|line| String::from(line.trim())),
+        );
+    }
+
+    let paragraph: Vec<String> = doc_lines
+        .into_iter()
+        .skip_while(String::is_empty)
+        .take_while(|line| !line.is_empty())
+        .collect();
+
+    let span = first_span?;
+    (!paragraph.is_empty()).then(|| LitStr::new(&paragraph.join(" "), span))
+}
+
+pub(crate) fn cfg_attrs(attrs: &[Attribute]) -> Vec<Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .cloned()
+        .collect()
+}
+
+pub(crate) fn take_error_attrs(attrs: &mut Vec<Attribute>) -> Vec<Attribute> {
+    let (error_attrs, other_attrs): (Vec<_>, Vec<_>) = mem::take(attrs)
+        .into_iter()
+        .partition(|attr| attr.path().is_ident("error"));
+    *attrs = other_attrs;
+
+    error_attrs
 }
 
 pub(crate) fn remove_generic_default(param: &mut GenericParam) {
@@ -103,19 +186,39 @@ pub(crate) fn generic_reduced_to_ident(
     }
 }
 
-pub(crate) fn add_debug_trait_bound(param: &mut GenericParam) {
-    use GenericParam as GP;
-    if let GP::Type(ref mut type_p) = *param {
-        #[expect(clippy::unwrap_used, reason = "this `TokenStream` is valid")]
-        let trait_path: Path =
-            syn::parse2(quote! { ::core::fmt::Debug }).unwrap();
-
-        type_p.colon_token = Some(Colon(type_p.span()));
-        type_p.bounds.push(TypeParamBound::Trait(TraitBound {
-            paren_token: None,
-            modifier: TraitBoundModifier::None,
-            lifetimes: None,
-            path: trait_path,
-        }));
+pub(crate) fn extend_where_clause(
+    generics: &Generics,
+    predicates: Vec<WherePredicate>,
+) -> Option<WhereClause> {
+    if predicates.is_empty() {
+        return generics.where_clause.clone();
     }
+
+    let mut where_clause =
+        generics
+            .where_clause
+            .clone()
+            .unwrap_or_else(|| WhereClause {
+                where_token: Where::default(),
+                predicates: Punctuated::new(),
+            });
+    where_clause.predicates.extend(predicates);
+
+    Some(where_clause)
+}
+
+pub(crate) fn self_debug_predicate() -> WherePredicate {
+    #[expect(clippy::unwrap_used, reason = "this `TokenStream` is valid")]
+    syn::parse2(quote! { Self: ::core::fmt::Debug }).unwrap()
+}
+
+pub(crate) fn tokens_mention_any(
+    tokens: TokenStream2,
+    idents: &[&Ident],
+) -> bool {
+    tokens.into_iter().any(|tree| match tree {
+        TokenTree::Ident(ident) => idents.contains(&&ident),
+        TokenTree::Group(group) => tokens_mention_any(group.stream(), idents),
+        TokenTree::Punct(_) | TokenTree::Literal(_) => false,
+    })
 }
@@ -0,0 +1,63 @@
+#!/usr/bin/env bash
+# Times `cargo check` of a generated enum with 1000 variants, each with a
+# `display` template, to measure how long the derive takes to expand.
+#
+# Usage: scripts/bench.sh [PACKAGE_DIR] [RUNS]
+#
+# PACKAGE_DIR is the directory of the `error-stack-macros2` package to measure,
+# `impl` in this repository by default. The macro and its dependencies are
+# built once before timing, so every run only expands and checks the generated
+# enum. Results are appended to `bench_output.txt`.
+
+set -euo pipefail
+
+root="$(cd "$(dirname "$0")/.." && pwd)"
+package_dir="$(cd "${1:-$root/impl}" && pwd)"
+runs="${2:-3}"
+variants=1000
+
+bench_dir="$root/target/bench"
+output="$root/bench_output.txt"
+
+mkdir -p "$bench_dir/src"
+
+cat >"$bench_dir/Cargo.toml" <<EOF
+[package]
+name = "error-stack-macros2-bench"
+version = "0.0.0"
+edition = "2024"
+publish = false
+
+[dependencies]
+error-stack-macros2 = { path = "$package_dir" }
+
+[workspace]
+EOF
+
+{
+	echo "use error_stack_macros2::Error;"
+	echo
+	echo "#[derive(Debug, Error)]"
+	echo "pub enum BenchError {"
+	for ((i = 0; i < variants; i++)); do
+		echo "    #[display(\"variant $i failed with code {code}: {name:?} ({attempts:>4})\")]"
+		echo "    Variant$i { code: u32, name: &'static str, attempts: u8 },"
+	done
+	echo "}"
+} >"$bench_dir/src/lib.rs"
+
+cd "$bench_dir"
+rm -f Cargo.lock
+cargo check --quiet
+
+{
+	echo "# $package_dir ($(rustc --version))"
+	for ((run = 1; run <= runs; run++)); do
+		touch src/lib.rs
+		start="$(date +%s.%N)"
+		cargo check --quiet
+		end="$(date +%s.%N)"
+		awk -v run="$run" -v start="$start" -v end="$end" \
+			'BEGIN { printf "run %d: %.2f s\n", run, end - start }'
+	done
+} | tee -a "$output"
@@ -124,4 +124,114 @@ mod tests {
             "tuple variant: point 15 units in front of the origin, and with x and y coords (5, 10)"
         );
     }
+
+    #[test]
+    fn tuple_variant_works_with_width_argument() {
+        #[derive(Debug, Error)]
+        enum EnumType {
+            #[display("tuple variant: [{0:>1$}] {{{1}}}")]
+            Tuple(&'static str, usize),
+        }
+
+        let test_val = EnumType::Tuple("ab", 4);
+        assert_eq!(test_val.to_string(), "tuple variant: [  ab] {4}");
+    }
+
+    #[test]
+    #[expect(
+        clippy::used_underscore_binding,
+        reason = "this test reads the binding of an unnamed field in a format argument"
+    )]
+    fn variants_work_with_extra_format_args() {
+        #[derive(Debug, Error)]
+        enum EnumType {
+            #[display(
+                "tuple variant: {} items, first is {first:?}",
+                _field0.len(),
+                first = _field0.first(),
+            )]
+            Tuple(Vec<u8>),
+
+            #[display("named field variant: {name} has {} characters", name.len())]
+            NamedFields { name: String },
+        }
+
+        let tuple = EnumType::Tuple(vec![7, 8, 9]);
+        assert_eq!(
+            tuple.to_string(),
+            "tuple variant: 3 items, first is Some(7)"
+        );
+
+        let named_fields = EnumType::NamedFields {
+            name: String::from("hello"),
+        };
+        assert_eq!(
+            named_fields.to_string(),
+            "named field variant: hello has 5 characters"
+        );
+    }
+
+    #[test]
+    fn variants_work_with_field_paths() {
+        #[derive(Debug)]
+        struct Request {
+            id: u32,
+        }
+
+        #[derive(Debug, Error)]
+        enum EnumType {
+            #[display("tuple variant: {0.id} and {1.len()} items")]
+            Tuple(Request, Vec<u8>),
+
+            #[display("named field variant: {req.id}")]
+            NamedFields { req: Request },
+        }
+
+        let tuple = EnumType::Tuple(Request { id: 7 }, vec![1, 2]);
+        assert_eq!(tuple.to_string(), "tuple variant: 7 and 2 items");
+
+        let named_fields = EnumType::NamedFields {
+            req: Request { id: 9 },
+        };
+        assert_eq!(named_fields.to_string(), "named field variant: 9");
+    }
+
+    #[test]
+    fn transparent_variants_work() {
+        #[derive(Debug, Error)]
+        enum EnumType {
+            #[display(transparent)]
+            Io(std::io::Error),
+
+            #[display(transparent)]
+            Fmt { source: std::fmt::Error },
+        }
+
+        let io = EnumType::Io(std::io::Error::other("disk full"));
+        assert_eq!(io.to_string(), "disk full");
+
+        let fmt = EnumType::Fmt {
+            source: std::fmt::Error,
+        };
+        assert_eq!(fmt.to_string(), std::fmt::Error.to_string());
+    }
+
+    #[test]
+    fn generic_variants_work_with_inferred_bounds() {
+        #[derive(Debug, Error)]
+        enum EnumType<T, U> {
+            #[display("tuple variant: {0}")]
+            Tuple(T),
+
+            #[display("named field variant: {inner:?}")]
+            NamedFields { inner: U },
+        }
+
+        let tuple = EnumType::<u8, Vec<u8>>::Tuple(5);
+        assert_eq!(tuple.to_string(), "tuple variant: 5");
+
+        let named_fields =
+            EnumType::<u8, Vec<u8>>::NamedFields { inner: vec![1, 2] };
+        assert_eq!(named_fields.to_string(), "named field variant: [1, 2]");
+    }
 }
@@ -1,5 +1,7 @@
 #[cfg(test)]
 mod tests {
+    use std::fmt::{self, Debug, Formatter};
+
     use error_stack_macros2::Error;
 
     #[test]
@@ -9,12 +11,37 @@ mod tests {
     }
 
     #[test]
+    #[expect(
+        deprecated,
+        reason = "the derive warns about the unused `display` attribute with a deprecated item"
+    )]
     fn empty_enum_works_with_display_attr() {
         #[derive(Debug, Error)]
         #[display("this display attr is unnecessary")]
         enum _EmptyEnumType {}
     }
 
+    #[test]
+    fn uninhabited_enum_works_without_display_attr() {
+        enum Void {}
+
+        #[derive(Debug, Error)]
+        enum _UninhabitedEnumType {
+            Tuple(std::convert::Infallible),
+            NamedFields {
+                _length: usize,
+                #[error(uninhabited)]
+                _void: Void,
+            },
+        }
+
+        impl Debug for Void {
+            fn fmt(&self, _: &mut Formatter<'_>) -> fmt::Result {
+                match *self {}
+            }
+        }
+    }
+
     #[test]
     #[expect(
         dead_code,
@@ -175,4 +202,407 @@ mod tests {
             "tuple variant: point 15 units in front of the origin, and with x and y coords (5, 10)"
         );
     }
+
+    #[test]
+    fn enum_works_with_display_attr_default_placeholders() {
+        #[derive(Debug, Error)]
+        #[display("database error in {table}")]
+        enum EnumType {
+            #[display("connection lost")]
+            Connection,
+
+            Query {
+                table: String,
+                query: String,
+            },
+
+            Insert {
+                table: String,
+            },
+
+            #[display("{0} rows rejected")]
+            Rejected(usize),
+        }
+
+        let query = EnumType::Query {
+            table: String::from("users"),
+            query: String::from("SELECT *"),
+        };
+        assert_eq!(query.to_string(), "database error in users");
+
+        let connection = EnumType::Connection;
+        assert_eq!(connection.to_string(), "connection lost");
+
+        let rejected = EnumType::Rejected(3);
+        assert_eq!(rejected.to_string(), "3 rows rejected");
+
+        let insert = EnumType::Insert {
+            table: String::from("orders"),
+        };
+        assert_eq!(insert.to_string(), "database error in orders");
+    }
+
+    #[test]
+    fn enum_works_with_intrinsic_placeholders() {
+        #[derive(Debug, Error)]
+        #[display("{type}::{variant} failed")]
+        enum EnumType {
+            Connect,
+
+            Query(String),
+
+            #[display("{type}::Lookup failed on {r#variant}")]
+            Lookup {
+                variant: u8,
+            },
+        }
+
+        assert_eq!(EnumType::Connect.to_string(), "EnumType::Connect failed");
+
+        let query = EnumType::Query(String::from("SELECT *"));
+        assert_eq!(query.to_string(), "EnumType::Query failed");
+
+        let lookup = EnumType::Lookup { variant: 2 };
+        assert_eq!(lookup.to_string(), "EnumType::Lookup failed on 2");
+    }
+
+    #[test]
+    fn enum_works_with_auto_display() {
+        #[derive(Debug, Error)]
+        #[display(auto, prefix = "storage: ")]
+        enum EnumType {
+            NotFound,
+
+            PermissionDenied {
+                path: String,
+            },
+
+            #[display(auto, case = "SCREAMING_SNAKE_CASE")]
+            InvalidInput(u8),
+
+            #[display("disk full")]
+            DiskFull,
+        }
+
+        assert_eq!(EnumType::NotFound.to_string(), "storage: not found");
+
+        let denied = EnumType::PermissionDenied {
+            path: String::from("/etc"),
+        };
+        assert_eq!(denied.to_string(), "storage: permission denied");
+
+        assert_eq!(EnumType::InvalidInput(0).to_string(), "INVALID_INPUT");
+        assert_eq!(EnumType::DiskFull.to_string(), "disk full");
+    }
+
+    #[test]
+    fn enum_works_with_doc_comments() {
+        #[derive(Debug, Error)]
+        #[display(doc)]
+        enum EnumType {
+            /// the requested item was not found
+            NotFound,
+
+            /// permission denied for
+            /// `{path}`
+            ///
+            /// This paragraph is not part of the message.
+            PermissionDenied { path: String },
+
+            #[display("disk full")]
+            DiskFull,
+        }
+
+        assert_eq!(
+            EnumType::NotFound.to_string(),
+            "the requested item was not found"
+        );
+
+        let denied = EnumType::PermissionDenied {
+            path: String::from("/etc"),
+        };
+        assert_eq!(denied.to_string(), "permission denied for `/etc`");
+        assert_eq!(EnumType::DiskFull.to_string(), "disk full");
+    }
+
+    #[test]
+    fn enum_works_with_namespaced_attrs() {
+        #[derive(Debug, Error)]
+        #[error_stack(display = "storage error")]
+        enum EnumType {
+            #[error_stack(display("file {0} not found"))]
+            NotFound(&'static str),
+
+            #[error_stack(transparent)]
+            Io(std::io::Error),
+
+            Other,
+        }
+
+        let not_found = EnumType::NotFound("data.bin");
+        assert_eq!(not_found.to_string(), "file data.bin not found");
+
+        let io = EnumType::Io(std::io::Error::other("disk full"));
+        assert_eq!(io.to_string(), "disk full");
+
+        assert_eq!(EnumType::Other.to_string(), "storage error");
+    }
+
+    #[test]
+    fn enum_works_with_cfg_attr_display() {
+        #[derive(Debug, Error)]
+        #[cfg_attr(unix, display("storage error"))]
+        #[cfg_attr(not(unix), display("storage failure"))]
+        enum EnumType {
+            #[cfg_attr(unix, display("unix socket closed"))]
+            #[cfg_attr(not(unix), display("named pipe closed"))]
+            Closed,
+
+            #[cfg_attr(windows, display("file not found"))]
+            NotFound,
+        }
+
+        let (closed, not_found) = if cfg!(windows) {
+            ("named pipe closed", "file not found")
+        } else if cfg!(unix) {
+            ("unix socket closed", "storage error")
+        } else {
+            ("named pipe closed", "storage failure")
+        };
+        assert_eq!(EnumType::Closed.to_string(), closed);
+        assert_eq!(EnumType::NotFound.to_string(), not_found);
+    }
+
+    #[test]
+    fn enum_works_with_style_lint() {
+        #[derive(Debug, Error)]
+        #[error_stack(style = "deny")]
+        #[display("storage error")]
+        enum EnumType {
+            #[display("HTTP request failed")]
+            Request,
+
+            #[display("{type}::{variant} failed")]
+            Other,
+        }
+
+        assert_eq!(EnumType::Request.to_string(), "HTTP request failed");
+        assert_eq!(EnumType::Other.to_string(), "EnumType::Other failed");
+    }
+
+    #[test]
+    fn enum_works_with_wrap_template() {
+        #[derive(Debug, Error)]
+        #[error(wrap = "storage: {message} {{retry}}")]
+        #[display("unknown failure")]
+        enum EnumType {
+            #[display("missing key {0}")]
+            Missing(u32),
+
+            #[display(transparent)]
+            Io(std::io::Error),
+
+            #[display(auto)]
+            TimedOut,
+
+            Other,
+        }
+
+        assert_eq!(
+            EnumType::Missing(7).to_string(),
+            "storage: missing key 7 {retry}"
+        );
+        assert_eq!(
+            EnumType::Io(std::io::Error::other("disk full")).to_string(),
+            "storage: disk full {retry}"
+        );
+        assert_eq!(
+            EnumType::TimedOut.to_string(),
+            "storage: timed out {retry}"
+        );
+        assert_eq!(
+            EnumType::Other.to_string(),
+            "storage: unknown failure {retry}"
+        );
+    }
+
+    #[test]
+    fn enum_works_with_wrap_template_placeholders() {
+        #[derive(Debug, Error)]
+        #[error(wrap = "{type}::{variant}: {message:>8}|")]
+        #[display("unknown")]
+        enum EnumType {
+            #[display("missing key {0}")]
+            Missing(u32),
+
+            #[display("full")]
+            Full,
+
+            Other,
+        }
+
+        #[derive(Debug, Error)]
+        #[error(wrap = "{variant}: {message}", message_fn)]
+        #[display("unknown")]
+        enum StaticEnumType {
+            #[display("full")]
+            Full,
+
+            Other,
+        }
+
+        assert_eq!(
+            EnumType::Missing(7).to_string(),
+            "EnumType::Missing: missing key 7|"
+        );
+        assert_eq!(EnumType::Full.to_string(), "EnumType::Full:     full|");
+        assert_eq!(EnumType::Other.to_string(), "EnumType::Other:  unknown|");
+        assert_eq!(StaticEnumType::Full.message(), "Full: full");
+        assert_eq!(StaticEnumType::Other.message(), "Other: unknown");
+    }
+
+    #[test]
+    fn enum_works_with_prefix() {
+        #[derive(Debug, Error)]
+        #[error_stack(prefix = "{storage} ")]
+        enum EnumType {
+            #[error_stack(display = "missing key")]
+            Missing,
+        }
+
+        assert_eq!(EnumType::Missing.to_string(), "{storage} missing key");
+    }
+
+    #[test]
+    fn enum_works_with_alt_template() {
+        #[derive(Debug, Error)]
+        #[display("storage error", alt = "{type}::{variant} storage error")]
+        enum EnumType {
+            #[display("missing key", alt = "missing key {0}")]
+            Missing(u32),
+
+            #[error_stack(display("full"))]
+            Full,
+
+            Other,
+        }
+
+        assert_eq!(format!("{}", EnumType::Missing(7)), "missing key");
+        assert_eq!(format!("{:#}", EnumType::Missing(7)), "missing key 7");
+        assert_eq!(format!("{:#}", EnumType::Full), "full");
+        assert_eq!(format!("{}", EnumType::Other), "storage error");
+        assert_eq!(
+            format!("{:#}", EnumType::Other),
+            "EnumType::Other storage error"
+        );
+    }
+
+    #[test]
+    fn enum_honors_width_and_precision() {
+        #[derive(Debug, Error)]
+        #[display("storage error", alt = "{type} storage error")]
+        enum EnumType {
+            #[display("missing key {0}")]
+            Missing(u32),
+
+            #[display(auto)]
+            TimedOut,
+
+            Other,
+        }
+
+        #[derive(Debug, Error)]
+        #[error(prefix = "storage: ")]
+        enum WrappedEnumType {
+            #[display("full")]
+            Full,
+        }
+
+        assert_eq!(
+            format!("{:>16}|", EnumType::Missing(7)),
+            "   missing key 7|"
+        );
+        assert_eq!(format!("{:-<12}", EnumType::TimedOut), "timed out---");
+        assert_eq!(format!("{:.7}", EnumType::Other), "storage");
+        assert_eq!(
+            format!("{:*>#24}", EnumType::Other),
+            "**EnumType storage error"
+        );
+        assert_eq!(format!("{:^15}", WrappedEnumType::Full), " storage: full ");
+    }
+
+    #[test]
+    fn enum_message_is_available_at_compile_time() {
+        #[derive(Debug, Error)]
+        #[error(prefix = "storage: ", message_fn)]
+        #[display("unknown failure")]
+        enum StaticEnumType {
+            #[display("full")]
+            Full,
+
+            #[display(auto)]
+            TimedOut,
+
+            Other,
+        }
+
+        #[derive(Debug, Error)]
+        #[error(message_fn)]
+        enum EnumType {
+            #[display("missing key {0}")]
+            Missing(u32),
+
+            #[display("full")]
+            Full,
+        }
+
+        const MESSAGE: &str = StaticEnumType::TimedOut.message();
+
+        assert_eq!(MESSAGE, "storage: timed out");
+        assert_eq!(StaticEnumType::Full.message(), "storage: full");
+        assert_eq!(
+            StaticEnumType::Other.message(),
+            StaticEnumType::Other.to_string()
+        );
+        assert_eq!(EnumType::Missing(7).message(), None);
+        assert_eq!(EnumType::Full.message(), Some("full"));
+    }
+
+    #[test]
+    fn enum_works_with_debug_impl() {
+        #[derive(Error)]
+        #[error(debug = "derive")]
+        enum EnumType {
+            #[display("missing key {0}")]
+            Missing(u32),
+
+            #[display("{path} is full")]
+            Full { path: u32 },
+
+            #[display("timed out")]
+            TimedOut,
+        }
+
+        #[derive(Error)]
+        #[error_stack(debug = "display")]
+        enum DisplayEnumType {
+            #[display("missing key {0}")]
+            Missing(u32),
+        }
+
+        assert_eq!(format!("{:?}", EnumType::Missing(7)), "Missing(7)");
+        assert_eq!(
+            format!("{:?}", EnumType::Full { path: 3 }),
+            "Full { path: 3 }"
+        );
+        assert_eq!(format!("{:?}", EnumType::TimedOut), "TimedOut");
+        assert_eq!(
+            format!("{:#?}", EnumType::Missing(7)),
+            "Missing(\n    7,\n)"
+        );
+        assert_eq!(
+            format!("{:?}", DisplayEnumType::Missing(7)),
+            "missing key 7"
+        );
+    }
 }
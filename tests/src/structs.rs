@@ -1,6 +1,10 @@
 #[cfg(test)]
 mod tests {
-    use std::fmt::{Debug, Display};
+    use std::{
+        cell::Cell,
+        fmt::{Debug, Display},
+        marker::PhantomData,
+    };
 
     use error_stack_macros2::Error;
 
@@ -97,6 +101,7 @@ mod tests {
         #[display(
             "string_a = {string_a}, string_b = {string_b}, slice = {slice:?}"
         )]
+        #[error(no_context_check)]
         struct NamedFieldStructType<'a, 'b: 'a> {
             string_a: &'a str,
             string_b: &'b str,
@@ -191,4 +196,509 @@ mod tests {
             "tuple struct: point 15 units in front of the origin, and with x and y coords (5, 10)"
         );
     }
+
+    #[test]
+    fn struct_works_with_escaped_braces() {
+        #[derive(Debug, Error)]
+        #[display("{{name}} is {name}, {{{{}}}}")]
+        struct NamedFieldStructType {
+            name: &'static str,
+        }
+
+        let test_val = NamedFieldStructType { name: "value" };
+        assert_eq!(test_val.to_string(), "{name} is value, {{}}");
+    }
+
+    #[test]
+    fn struct_works_with_format_specs() {
+        #[derive(Debug, Error)]
+        #[display(
+            "[{value:*^+9.2}] [{value:>width$.precision$}] [{width:#06x}]"
+        )]
+        struct NamedFieldStructType {
+            value: f64,
+            width: usize,
+            precision: usize,
+        }
+
+        let test_val = NamedFieldStructType {
+            value: 1.5,
+            width: 6,
+            precision: 1,
+        };
+        assert_eq!(test_val.to_string(), "[**+1.50**] [   1.5] [0x0006]");
+    }
+
+    #[test]
+    fn tuple_struct_works_with_width_argument() {
+        #[derive(Debug, Error)]
+        #[display("[{0:>1$}] [{0:<1$}]")]
+        struct TupleStructType(&'static str, usize);
+
+        let test_val = TupleStructType("ab", 4);
+        assert_eq!(test_val.to_string(), "[  ab] [ab  ]");
+    }
+
+    #[test]
+    fn struct_works_with_raw_identifier_fields() {
+        #[derive(Debug, Error)]
+        #[display("type = {r#type:?}")]
+        struct NamedFieldStructType {
+            r#type: &'static str,
+        }
+
+        let test_val = NamedFieldStructType { r#type: "raw" };
+        assert_eq!(test_val.to_string(), "type = \"raw\"");
+    }
+
+    #[test]
+    fn struct_works_with_intrinsic_placeholders() {
+        #[derive(Debug, Error)]
+        #[display("{type} in {module}: {name}")]
+        struct NamedFieldStructType {
+            name: &'static str,
+        }
+
+        let test_val = NamedFieldStructType { name: "config" };
+        assert_eq!(
+            test_val.to_string(),
+            format!("NamedFieldStructType in {}: config", module_path!())
+        );
+    }
+
+    #[test]
+    fn struct_works_with_doc_comment() {
+        /// invalid card string: {0:?}
+        #[derive(Debug, Error)]
+        #[display(doc)]
+        struct TupleStructType(&'static str);
+
+        let test_val = TupleStructType("1234");
+        assert_eq!(test_val.to_string(), "invalid card string: \"1234\"");
+    }
+
+    #[test]
+    fn struct_works_with_namespaced_attrs() {
+        #[derive(Debug, Error)]
+        #[error_stack(display("invalid value: {}", self.0 * 2), no_context_check)]
+        struct TupleStructType(u8);
+
+        let test_val = TupleStructType(21);
+        assert_eq!(test_val.to_string(), "invalid value: 42");
+    }
+
+    #[test]
+    fn struct_works_with_non_forwarded_attrs() {
+        /// Documentation that must not end up on the generated impls.
+        #[derive(Debug, Error)]
+        #[display("invalid value: {0}")]
+        #[repr(C)]
+        #[must_use]
+        struct TupleStructType(u8);
+
+        let test_val = TupleStructType(5);
+        assert_eq!(test_val.to_string(), "invalid value: 5");
+    }
+
+    #[test]
+    fn struct_works_with_extra_format_args() {
+        #[derive(Debug, Error)]
+        #[display(
+            "expected {} bytes, got {actual} ({:.precision$}%)",
+            self.expected.len(),
+            self.ratio * 100.0,
+            precision = 2,
+            actual = self.buf.len(),
+        )]
+        struct NamedFieldStructType {
+            expected: Vec<u8>,
+            buf: Vec<u8>,
+            ratio: f64,
+        }
+
+        let test_val = NamedFieldStructType {
+            expected: vec![0; 4],
+            buf: vec![0; 3],
+            ratio: 0.75,
+        };
+        assert_eq!(test_val.to_string(), "expected 4 bytes, got 3 (75.00%)");
+    }
+
+    #[test]
+    fn struct_works_with_field_paths() {
+        #[derive(Debug)]
+        struct Request {
+            id: u32,
+            host: &'static str,
+        }
+
+        #[derive(Debug, Error)]
+        #[display(
+            "request {req.id} to {req.host} failed after {items.len()} attempts"
+        )]
+        struct NamedFieldStructType {
+            req: Request,
+            items: Vec<u8>,
+        }
+
+        let test_val = NamedFieldStructType {
+            req: Request {
+                id: 42,
+                host: "example.com",
+            },
+            items: vec![1, 2, 3],
+        };
+        assert_eq!(
+            test_val.to_string(),
+            "request 42 to example.com failed after 3 attempts"
+        );
+    }
+
+    #[test]
+    fn transparent_struct_works() {
+        #[derive(Debug, Error)]
+        #[display(transparent)]
+        struct TupleStructType(std::io::Error);
+
+        let test_val = TupleStructType(std::io::Error::other("disk full"));
+        assert_eq!(test_val.to_string(), "disk full");
+        assert_eq!(format!("{test_val:>11}"), "  disk full");
+    }
+
+    #[test]
+    fn generic_struct_works_with_inferred_bounds() {
+        trait Entity {
+            type Id;
+        }
+
+        #[derive(Debug)]
+        struct User;
+
+        impl Entity for User {
+            type Id = u32;
+        }
+
+        #[derive(Debug, Error)]
+        #[display("{value} with id {id:#x}")]
+        struct NamedFieldStructType<T: Entity, V> {
+            value: V,
+            id: T::Id,
+        }
+
+        let test_val = NamedFieldStructType::<User, &str> {
+            value: "user",
+            id: 255,
+        };
+        assert_eq!(test_val.to_string(), "user with id 0xff");
+    }
+
+    #[test]
+    fn generic_struct_works_without_debug_phantom_parameter() {
+        struct NotDebug;
+
+        #[derive(Error)]
+        #[display("phantom struct")]
+        struct NamedFieldStructType<T> {
+            _data: PhantomData<T>,
+        }
+
+        impl<T> Debug for NamedFieldStructType<T> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("NamedFieldStructType")
+            }
+        }
+
+        fn assert_error<E: std::error::Error>(_: &E) {}
+
+        let test_val = NamedFieldStructType::<NotDebug> { _data: PhantomData };
+        assert_error(&test_val);
+        assert_eq!(test_val.to_string(), "phantom struct");
+    }
+
+    #[test]
+    fn generic_struct_works_with_bounded_debug_impl() {
+        #[derive(Error)]
+        #[display("wrapped {0}")]
+        struct TupleStructType<T: Display>(T);
+
+        impl<T: Clone + Display> Debug for TupleStructType<T> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("TupleStructType")
+            }
+        }
+
+        fn assert_error<E: std::error::Error>(_: &E) {}
+
+        let test_val = TupleStructType(5);
+        assert_error(&test_val);
+        assert_eq!(test_val.to_string(), "wrapped 5");
+    }
+
+    #[test]
+    fn generic_struct_works_with_bound_overrides() {
+        #[derive(Error)]
+        #[display("{value}")]
+        #[error(bound = "T: Display")]
+        struct NamedFieldStructType<T> {
+            value: T,
+        }
+
+        impl<T> Debug for NamedFieldStructType<T> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("NamedFieldStructType")
+            }
+        }
+
+        #[derive(Debug, Error)]
+        #[display("{0}")]
+        struct TupleStructType<#[error(add_bound = "Send + Sync + 'static")] T>(
+            T,
+        );
+
+        fn assert_error<E: std::error::Error + Send + Sync + 'static>(_: &E) {}
+
+        let test_val = NamedFieldStructType { value: 5 };
+        assert_error(&test_val);
+        assert_eq!(test_val.to_string(), "5");
+
+        let test_val_2 = TupleStructType("tuple");
+        assert_error(&test_val_2);
+        assert_eq!(test_val_2.to_string(), "tuple");
+    }
+
+    #[test]
+    fn struct_works_with_context_check_disabled() {
+        #[derive(Debug, Error)]
+        #[display("shared name: {name}")]
+        #[error(no_context_check)]
+        struct NamedFieldStructType {
+            name: std::rc::Rc<str>,
+        }
+
+        let test_val = NamedFieldStructType {
+            name: std::rc::Rc::from("hello"),
+        };
+        assert_eq!(test_val.to_string(), "shared name: hello");
+    }
+
+    #[test]
+    fn uninhabited_struct_works_without_display_attr() {
+        #[derive(Debug, Error)]
+        struct _TupleStructType(u8, std::convert::Infallible);
+    }
+
+    #[test]
+    fn struct_works_with_cfg_attr_display() {
+        #[derive(Debug, Error)]
+        #[cfg_attr(unix, display("unix socket closed"))]
+        #[cfg_attr(not(unix), display("named pipe closed"))]
+        struct UnitStructType;
+
+        let expected = if cfg!(unix) {
+            "unix socket closed"
+        } else {
+            "named pipe closed"
+        };
+        assert_eq!(UnitStructType.to_string(), expected);
+    }
+
+    #[test]
+    fn struct_works_with_exclusive_cfg_attr_display() {
+        #[derive(Debug, Error)]
+        #[cfg_attr(windows, display("named pipe closed"))]
+        #[cfg_attr(not(windows), display("unix socket closed"))]
+        struct UnitStructType;
+
+        let expected = if cfg!(windows) {
+            "named pipe closed"
+        } else {
+            "unix socket closed"
+        };
+        assert_eq!(UnitStructType.to_string(), expected);
+    }
+
+    #[test]
+    fn struct_works_in_strict_mode() {
+        #[derive(Debug, Error)]
+        #[error_stack(strict, display = "{type} could not be parsed")]
+        struct UnitStructType;
+
+        #[derive(Debug, Error)]
+        #[error(strict = "allow")]
+        #[display("{0} rows rejected")]
+        struct TupleStructType(usize);
+
+        assert_eq!(
+            UnitStructType.to_string(),
+            "UnitStructType could not be parsed"
+        );
+        assert_eq!(TupleStructType(3).to_string(), "3 rows rejected");
+    }
+
+    #[test]
+    fn struct_works_with_alt_template() {
+        #[derive(Debug, Error)]
+        #[display("request failed", alt = "request to {host} failed after {} attempts", self.attempts)]
+        struct NamedStructType {
+            host: &'static str,
+            attempts: u8,
+        }
+
+        #[derive(Debug, Error)]
+        #[display("{type} failed")]
+        struct UnitStructType;
+
+        let err = NamedStructType {
+            host: "example.com",
+            attempts: 3,
+        };
+        assert_eq!(format!("{err}"), "request failed");
+        assert_eq!(
+            format!("{err:#}"),
+            "request to example.com failed after 3 attempts"
+        );
+        assert_eq!(format!("{UnitStructType:#}"), "UnitStructType failed");
+    }
+
+    #[test]
+    fn struct_honors_width_and_precision() {
+        #[derive(Debug, Error)]
+        #[display("connection closed")]
+        struct UnitStructType;
+
+        #[derive(Debug, Error)]
+        #[display("{0} rows rejected")]
+        struct TupleStructType(usize);
+
+        #[derive(Debug, Error)]
+        #[display(transparent)]
+        struct TransparentStructType(&'static str);
+
+        #[derive(Debug, Error)]
+        #[error(no_padding)]
+        #[display("{0} rows rejected")]
+        struct UnpaddedStructType(usize);
+
+        assert_eq!(format!("{UnitStructType:>20}"), "   connection closed");
+        assert_eq!(format!("{UnitStructType:.10}"), "connection");
+        assert_eq!(
+            format!("{:*^20}", TupleStructType(3)),
+            "**3 rows rejected***"
+        );
+        assert_eq!(format!("{:<8.4}|", TupleStructType(3)), "3 ro    |");
+        assert_eq!(format!("{:>6}", TransparentStructType("eof")), "   eof");
+        assert_eq!(format!("{:>20}", UnpaddedStructType(3)), "3 rows rejected");
+    }
+
+    #[test]
+    fn struct_padding_formats_fields_once() {
+        struct Counted<'a>(&'a Cell<usize>);
+
+        impl Display for Counted<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.set(self.0.get().saturating_add(1));
+                f.write_str("counted")
+            }
+        }
+
+        #[derive(Debug, Error)]
+        #[error(no_context_check)]
+        #[display("{counter} value")]
+        struct NamedStructType<'a> {
+            counter: Counted<'a>,
+        }
+
+        impl Debug for Counted<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("Counted")
+            }
+        }
+
+        let calls = Cell::new(0);
+        let err = NamedStructType {
+            counter: Counted(&calls),
+        };
+        assert_eq!(format!("{err:>15}"), "  counted value");
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn struct_padding_streams_long_messages() {
+        #[derive(Debug, Error)]
+        #[display("{0}!")]
+        struct TupleStructType(String);
+
+        let long = "x".repeat(300);
+        let err = TupleStructType(long.clone());
+        assert_eq!(format!("{err:>10}"), format!("{long}!"));
+        assert_eq!(format!("{err:.280}"), "x".repeat(280));
+        assert_eq!(format!("{err:-<305}"), format!("{long}!----"));
+    }
+
+    #[test]
+    fn struct_message_is_available_at_compile_time() {
+        #[derive(Debug, Error)]
+        #[error(message_fn)]
+        #[display("connection {{closed}}")]
+        struct UnitStructType;
+
+        #[derive(Debug, Error)]
+        #[error(message_fn)]
+        #[display("{0} rows rejected")]
+        struct TupleStructType(usize);
+
+        const MESSAGE: &str = UnitStructType.message();
+
+        assert_eq!(MESSAGE, "connection {closed}");
+        assert_eq!(MESSAGE, UnitStructType.to_string());
+        assert_eq!(TupleStructType(3).message(), None);
+    }
+
+    #[test]
+    fn struct_can_define_its_own_message_method() {
+        #[derive(Debug, Error)]
+        #[display("connection closed")]
+        struct TupleStructType(u16);
+
+        impl TupleStructType {
+            const fn message(&self) -> u16 {
+                self.0
+            }
+        }
+
+        assert_eq!(TupleStructType(7).message(), 7);
+        assert_eq!(TupleStructType(7).to_string(), "connection closed");
+    }
+
+    #[test]
+    fn struct_works_with_debug_impl() {
+        #[derive(Error)]
+        #[error(debug = "derive")]
+        #[display("{path} not found")]
+        struct NamedFieldStructType<T> {
+            path: &'static str,
+            r#retries: T,
+        }
+
+        #[derive(Error)]
+        #[error_stack(debug = "display")]
+        #[display("{0} rows rejected")]
+        struct TupleStructType(usize);
+
+        #[derive(Error)]
+        #[error(debug = "derive")]
+        #[display("connection closed")]
+        struct UnitStructType;
+
+        let named = NamedFieldStructType {
+            path: "config.toml",
+            retries: 3_u8,
+        };
+        assert_eq!(
+            format!("{named:?}"),
+            r#"NamedFieldStructType { path: "config.toml", retries: 3 }"#
+        );
+        assert_eq!(format!("{:?}", TupleStructType(3)), "3 rows rejected");
+        assert_eq!(format!("{UnitStructType:?}"), "UnitStructType");
+    }
 }